@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use game::{bitboard::Bitboard, Game, Player};
+
+/// Plays out every cell of the board in order, the way a search walks down a line.
+fn fill_game(game: &Game) -> Game {
+    let mut game = game.clone();
+    while let Some(position) = game.legal_moves().first().copied() {
+        game.play(position).unwrap();
+    }
+    game
+}
+
+fn fill_bitboard(bitboard: &Bitboard) -> Bitboard {
+    let mut bitboard = bitboard.clone();
+    while let Some(cell) = bitboard.moves().next() {
+        bitboard.make_move(cell);
+        if bitboard.wins_at(cell) { break; }
+    }
+    bitboard
+}
+
+/// Counts every move sequence to a fixed depth, making and unmaking moves on the bitboard.
+fn perft_bitboard(bitboard: &mut Bitboard, depth: u32) -> u64 {
+    if depth == 0 { return 1; }
+    let mut nodes = 0;
+    let moves: Vec<usize> = bitboard.moves().collect();
+    for cell in moves {
+        bitboard.make_move(cell);
+        nodes += if bitboard.wins_at(cell) { 1 } else { perft_bitboard(bitboard, depth - 1) };
+        bitboard.unmake_move(cell);
+    }
+    nodes
+}
+
+/// The same count with the array board, which has to be cloned for every move.
+fn perft_game(game: &Game, depth: u32) -> u64 {
+    if depth == 0 { return 1; }
+    game.legal_moves().into_iter().map(|position| {
+        let mut next = game.clone();
+        next.play(position).unwrap();
+        if next.is_over() { 1 } else { perft_game(&next, depth - 1) }
+    }).sum()
+}
+
+fn benchmark(c: &mut Criterion) {
+    let game = Game::new(Player::X);
+    let bitboard = Bitboard::from_game(&game).unwrap();
+    c.bench_function("array fill 3x3", |b| b.iter(|| fill_game(black_box(&game))));
+    c.bench_function("bitboard fill 3x3", |b| b.iter(|| fill_bitboard(black_box(&bitboard))));
+    c.bench_function("array perft 3x3 depth 5", |b| b.iter(|| perft_game(black_box(&game), 5)));
+    c.bench_function("bitboard perft 3x3 depth 5", |b| b.iter(|| perft_bitboard(&mut black_box(bitboard.clone()), 5)));
+
+    let game = Game::with_size(8, 8, 5, Player::X).unwrap();
+    let bitboard = Bitboard::from_game(&game).unwrap();
+    c.bench_function("array perft 8x8 depth 3", |b| b.iter(|| perft_game(black_box(&game), 3)));
+    c.bench_function("bitboard perft 8x8 depth 3", |b| b.iter(|| perft_bitboard(&mut black_box(bitboard.clone()), 3)));
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
@@ -0,0 +1,144 @@
+use std::{env, process};
+use game::{engine::{Engine, MinimaxEngine, RandomEngine}, mcts::{MctsConfig, MctsEngine}, Game, Player};
+use stats::{GameResult, Summary};
+
+#[cfg(test)]
+mod tests;
+mod stats;
+
+const USAGE: &str = "usage: arena [--a ENGINE] [--b ENGINE] [--games N] [--seed SEED] [--board WIDTHxHEIGHTxWIN] [--format json|csv]
+engines: minimax, random, mcts or mcts:ITERATIONS";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EngineSpec {
+    Minimax,
+    Random,
+    Mcts(u32),  // iterations per move
+}
+
+impl EngineSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            None if spec == "minimax" => Ok(EngineSpec::Minimax),
+            None if spec == "random" => Ok(EngineSpec::Random),
+            None if spec == "mcts" => Ok(EngineSpec::Mcts(MctsConfig::default().iterations.unwrap_or(0))),
+            Some(("mcts", iterations)) => iterations.parse().map(EngineSpec::Mcts)
+                .map_err(|_| format!("invalid iteration count {}", iterations)),
+            _ => Err(format!("unknown engine {}", spec)),
+        }
+    }
+    pub fn name(&self) -> String {
+        match self {
+            EngineSpec::Minimax => "minimax".to_owned(),
+            EngineSpec::Random => "random".to_owned(),
+            EngineSpec::Mcts(iterations) => format!("mcts:{}", iterations),
+        }
+    }
+    /// A fresh engine, seeded so every game can be replayed.
+    pub fn engine(&self, seed: u64) -> Box<dyn Engine> {
+        match self {
+            EngineSpec::Minimax => Box::new(MinimaxEngine::new()),
+            EngineSpec::Random => Box::new(RandomEngine::new(seed)),
+            EngineSpec::Mcts(iterations) => Box::new(MctsEngine::new(MctsConfig {
+                iterations: Some(*iterations),
+                seed,
+                ..Default::default()
+            })),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub a: EngineSpec,
+    pub b: EngineSpec,
+    pub games: u32,
+    pub seed: u64,
+    pub board: (usize, usize, usize),   // width, height and win length
+    pub csv: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { a: EngineSpec::Minimax, b: EngineSpec::Random, games: 100, seed: 0, board: (3, 3, 3), csv: false }
+    }
+}
+
+impl Config {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(format!("missing value for {}", flag))?;
+            match flag.as_str() {
+                "--a" => config.a = EngineSpec::parse(value)?,
+                "--b" => config.b = EngineSpec::parse(value)?,
+                "--games" => config.games = value.parse().map_err(|_| format!("invalid game count {}", value))?,
+                "--seed" => config.seed = value.parse().map_err(|_| format!("invalid seed {}", value))?,
+                "--board" => {
+                    let sizes = value.split('x').map(str::parse).collect::<Result<Vec<usize>, _>>()
+                        .map_err(|_| format!("invalid board {}", value))?;
+                    let [width, height, win_length] = sizes[..] else { return Err(format!("invalid board {}", value)); };
+                    Game::with_size(width, height, win_length, Player::X)?;
+                    config.board = (width, height, win_length);
+                }
+                "--format" => config.csv = match value.as_str() {
+                    "json" => false,
+                    "csv" => true,
+                    _ => return Err(format!("unknown format {}", value)),
+                },
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        // the same limit lobbies put on the engine, minimax can't search anything bigger in reasonable time
+        let (width, height, _) = config.board;
+        if [config.a, config.b].contains(&EngineSpec::Minimax) && width * height > 9 {
+            return Err("the minimax engine can only search boards of up to 9 cells".to_owned());
+        }
+        Ok(config)
+    }
+}
+
+/// Plays one game with A as X and B as O, `a_starts` deciding who moves first.
+pub fn play_game(config: &Config, seed: u64, a_starts: bool) -> GameResult {
+    let (width, height, win_length) = config.board;
+    let starter = if a_starts { Player::X } else { Player::O };
+    let mut game = Game::with_size(width, height, win_length, starter).unwrap();
+    // the engines get different seeds so two random engines don't mirror each other
+    let (mut a, mut b) = (config.a.engine(seed), config.b.engine(seed ^ u64::MAX));
+    while let Some(player) = game.current_player {
+        let engine = if player == Player::X { &mut a } else { &mut b };
+        let Some(position) = engine.choose_move(&game) else { break; };
+        if game.play(position).is_err() {
+            // an illegal move loses the game for the engine that made it
+            game.forfeit(&player);
+        }
+    }
+    GameResult { seed, a_starts, winner: game.outcome.and_then(|outcome| outcome.winner()), moves: game.moves.len() }
+}
+
+/// Plays the whole match, alternating which engine starts.
+pub fn run(config: &Config) -> Summary {
+    let results = (0..config.games)
+        .map(|index| play_game(config, config.seed.wrapping_add(index as u64), index % 2 == 0))
+        .collect();
+    Summary::new(&config.a.name(), &config.b.name(), results)
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let config = Config::parse(&args).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(2);
+    });
+    let summary = run(&config);
+    if config.csv {
+        print!("{}", summary.to_csv());
+    } else {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::Serialize;
+use game::Player;
+
+/// The z-score for a 95% confidence interval.
+const Z: f64 = 1.96;
+
+/// A rate with its 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Estimate {
+    /// The Wilson score interval, which stays inside [0, 1] even for rates near the edges.
+    pub fn wilson(successes: u32, trials: u32) -> Self {
+        if trials == 0 { return Estimate { value: 0.0, low: 0.0, high: 1.0 }; }
+        let (n, p) = (trials as f64, successes as f64 / trials as f64);
+        let centre = (p + Z * Z / (2.0 * n)) / (1.0 + Z * Z / n);
+        let margin = Z / (1.0 + Z * Z / n) * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt();
+        Estimate { value: p, low: (centre - margin).max(0.0), high: (centre + margin).min(1.0) }
+    }
+    /// The normal approximation for the mean of per-game scores of 1, ½ and 0.
+    pub fn score(wins: u32, draws: u32, losses: u32) -> Self {
+        let n = (wins + draws + losses) as f64;
+        if n == 0.0 { return Estimate { value: 0.0, low: 0.0, high: 1.0 }; }
+        let mean = (wins as f64 + draws as f64 / 2.0) / n;
+        let variance = (wins as f64 * (1.0 - mean).powi(2) + draws as f64 * (0.5 - mean).powi(2)
+            + losses as f64 * mean.powi(2)) / n;
+        let margin = Z * (variance / n).sqrt();
+        Estimate { value: mean, low: (mean - margin).max(0.0), high: (mean + margin).min(1.0) }
+    }
+}
+
+/// One finished game, seen from engine A's side.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub a_starts: bool,
+    pub winner: Option<Player>,     // A plays X, B plays O
+    pub moves: usize,
+}
+
+/// The match totals from engine A's point of view.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Summary {
+    pub engine_a: String,
+    pub engine_b: String,
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub win_rate: Estimate,
+    pub draw_rate: Estimate,
+    pub loss_rate: Estimate,
+    pub score: Estimate,    // wins count 1, draws ½
+    pub results: Vec<GameResult>,
+}
+
+impl Summary {
+    pub fn new(engine_a: &str, engine_b: &str, results: Vec<GameResult>) -> Self {
+        let count = |winner: Option<Player>| results.iter().filter(|result| result.winner == winner).count() as u32;
+        let (wins, draws, losses) = (count(Some(Player::X)), count(None), count(Some(Player::O)));
+        let games = results.len() as u32;
+        Summary {
+            engine_a: engine_a.to_owned(),
+            engine_b: engine_b.to_owned(),
+            games, wins, draws, losses,
+            win_rate: Estimate::wilson(wins, games),
+            draw_rate: Estimate::wilson(draws, games),
+            loss_rate: Estimate::wilson(losses, games),
+            score: Estimate::score(wins, draws, losses),
+            results,
+        }
+    }
+    /// A header and a single row of totals, the per-game results are left to the JSON output.
+    pub fn to_csv(&self) -> String {
+        let header = "engine_a,engine_b,games,wins,draws,losses,\
+            win_rate,win_low,win_high,draw_rate,draw_low,draw_high,loss_rate,loss_low,loss_high,score,score_low,score_high";
+        let estimates = [self.win_rate, self.draw_rate, self.loss_rate, self.score].iter()
+            .map(|estimate| format!("{:.4},{:.4},{:.4}", estimate.value, estimate.low, estimate.high))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}\n{},{},{},{},{},{},{}\n", header, self.engine_a, self.engine_b, self.games, self.wins, self.draws, self.losses, estimates)
+    }
+}
@@ -0,0 +1,58 @@
+use game::Player;
+use crate::{play_game, run, stats::{Estimate, GameResult, Summary}, Config, EngineSpec};
+
+fn args(args: &str) -> Vec<String> {
+    args.split_whitespace().map(str::to_owned).collect()
+}
+
+#[test]
+fn test_parse_config() {
+    let config = Config::parse(&args("--a mcts:500 --b random --games 10 --seed 42 --board 4x4x3 --format csv")).unwrap();
+    assert_eq!(config, Config { a: EngineSpec::Mcts(500), b: EngineSpec::Random, games: 10, seed: 42, board: (4, 4, 3), csv: true });
+    assert_eq!(Config::parse(&[]).unwrap(), Config::default());
+    assert!(Config::parse(&args("--a stockfish")).is_err());
+    assert!(Config::parse(&args("--board 3x3")).is_err());
+    assert!(Config::parse(&args("--board 3x3x4")).is_err());
+    assert!(Config::parse(&args("--games")).is_err());
+    assert!(Config::parse(&args("--board 4x4x3")).is_err()); // minimax plays A by default
+    assert!(Config::parse(&args("--a random --b minimax --board 3x4x3")).is_err());
+}
+
+#[test]
+fn test_estimates() {
+    let estimate = Estimate::wilson(50, 100);
+    assert_eq!(estimate.value, 0.5);
+    assert!((estimate.low - 0.4038).abs() < 0.001 && (estimate.high - 0.5962).abs() < 0.001);
+    // no wins still leaves room for a small win rate
+    let estimate = Estimate::wilson(0, 10);
+    assert_eq!(estimate.low, 0.0);
+    assert!(estimate.high > 0.2);
+    let score = Estimate::score(0, 10, 0);
+    assert_eq!((score.value, score.low, score.high), (0.5, 0.5, 0.5));
+}
+
+#[test]
+fn test_match() {
+    let config = Config { games: 6, ..Default::default() };
+    let summary = run(&config);
+    assert_eq!(summary.games, 6);
+    assert_eq!(summary.losses, 0); // perfect play never loses
+    assert_eq!(summary.wins + summary.draws, 6);
+    assert_eq!(summary.results.iter().filter(|result| result.a_starts).count(), 3);
+    // the same seed replays the same game
+    assert_eq!(summary.results[1], play_game(&config, 1, false));
+
+    let csv = summary.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+    assert!(lines[1].starts_with("minimax,random,6,"));
+}
+
+#[test]
+fn test_summary_counts() {
+    let result = |winner| GameResult { seed: 0, a_starts: true, winner, moves: 5 };
+    let summary = Summary::new("a", "b", vec![result(Some(Player::X)), result(None), result(Some(Player::O)), result(Some(Player::X))]);
+    assert_eq!((summary.wins, summary.draws, summary.losses), (2, 1, 1));
+    assert_eq!(summary.score.value, 0.625);
+}
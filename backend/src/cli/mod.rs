@@ -0,0 +1,172 @@
+use std::{env, io::{self, BufRead, Write}, process, time::Duration};
+use game::{engine::{Engine, MinimaxEngine}, mcts::{MctsConfig, MctsEngine}, render::{RenderStyle, Renderer}, Game, Player};
+
+#[cfg(test)]
+mod tests;
+
+const USAGE: &str = "usage: cli [--ai x|o] [--first x|o] [--board WIDTHxHEIGHTxWIN] [--style ascii|unicode|compact]
+without --ai two players take turns at the same terminal";
+const HELP: &str = "enter a cell number counting from 1 at the top left, or coordinates like b2 (column letter, row number)
+undo  take back your last move
+help  show this message
+quit  end the game";
+/// How long the AI thinks per move on boards too big to search exhaustively.
+const AI_TIME_LIMIT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    Move(usize),
+    Undo,
+    Help,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub ai: Option<Player>,             // the symbol the AI plays, None for hot-seat
+    pub first: Player,
+    pub board: (usize, usize, usize),   // width, height and win length
+    pub renderer: Renderer,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config { ai: None, first: Player::X, board: (3, 3, 3), renderer: Renderer::default() }
+    }
+}
+
+fn parse_player(value: &str) -> Result<Player, String> {
+    match value.to_lowercase().as_str() {
+        "x" => Ok(Player::X),
+        "o" => Ok(Player::O),
+        _ => Err(format!("unknown player {}", value)),
+    }
+}
+
+impl Config {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut config = Config::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(format!("missing value for {}", flag))?;
+            match flag.as_str() {
+                "--ai" => config.ai = Some(parse_player(value)?),
+                "--first" => config.first = parse_player(value)?,
+                "--board" => {
+                    let sizes = value.split('x').map(str::parse).collect::<Result<Vec<usize>, _>>()
+                        .map_err(|_| format!("invalid board {}", value))?;
+                    let [width, height, win_length] = sizes[..] else { return Err(format!("invalid board {}", value)); };
+                    Game::with_size(width, height, win_length, Player::X)?;
+                    config.board = (width, height, win_length);
+                }
+                "--style" => config.renderer.style = match value.as_str() {
+                    "ascii" => RenderStyle::Ascii,
+                    "unicode" => RenderStyle::Unicode,
+                    "compact" => RenderStyle::Compact,
+                    _ => return Err(format!("unknown style {}", value)),
+                },
+                _ => return Err(format!("unknown option {}", flag)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Reads a line of input: a command, a cell number from 1 or coordinates like `b2`.
+pub fn parse_command(input: &str, width: usize, height: usize) -> Result<Command, String> {
+    let input = input.trim().to_lowercase();
+    match input.as_str() {
+        "u" | "undo" => return Ok(Command::Undo),
+        "h" | "help" | "?" => return Ok(Command::Help),
+        "q" | "quit" | "exit" => return Ok(Command::Quit),
+        _ => {}
+    }
+    let cells = width * height;
+    if let Ok(cell) = input.parse::<usize>() {
+        if !(1..=cells).contains(&cell) { return Err(format!("cells are numbered 1 to {}", cells)); }
+        return Ok(Command::Move(cell - 1));
+    }
+    let mut chars = input.chars();
+    let column = chars.next().filter(char::is_ascii_lowercase).map(|letter| (letter as u8 - b'a') as usize);
+    let row = chars.as_str().parse::<usize>().ok();
+    match (column, row) {
+        (Some(column), Some(row)) if column < width && (1..=height).contains(&row) => Ok(Command::Move((row - 1) * width + column)),
+        (Some(_), Some(_)) => Err(format!("{} is off the board", input)),
+        _ => Err(format!("couldn't read {}, type help for the commands", input)),
+    }
+}
+
+/// A cell as coordinates, the way they're typed in.
+pub fn coordinates(position: usize, width: usize) -> String {
+    format!("{}{}", (b'a' + (position % width) as u8) as char, position / width + 1)
+}
+
+/// The result and the moves that led to it.
+pub fn summary(game: &Game) -> String {
+    let result = match game.outcome.map(|outcome| (outcome.winner(), outcome)) {
+        Some((Some(winner), game::Outcome::Forfeit(_))) => format!("{} wins by forfeit", winner),
+        Some((Some(winner), _)) => format!("{} wins", winner),
+        Some((None, _)) => "Draw".to_owned(),
+        None => "Game abandoned".to_owned(),
+    };
+    let moves = game.moves.iter()
+        .map(|(player, position)| format!("{} {}", player, coordinates(*position, game.width)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("{} after {} moves\n{}", result, game.moves.len(), moves)
+}
+
+fn engine(game: &Game) -> Box<dyn Engine> {
+    if game.board.len() <= 9 { return Box::new(MinimaxEngine::new()); }
+    Box::new(MctsEngine::new(MctsConfig { time_limit: Some(AI_TIME_LIMIT), seed: rand::random(), ..Default::default() }))
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let config = Config::parse(&args).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(2);
+    });
+    let (width, height, win_length) = config.board;
+    let mut game = Game::with_size(width, height, win_length, config.first).unwrap();
+    let mut ai = engine(&game);
+    let mut lines = io::stdin().lock().lines();
+    println!("{}\n", HELP);
+    println!("{}\n", config.renderer.render(&game));
+    while let Some(player) = game.current_player {
+        if config.ai == Some(player) {
+            let Some(position) = ai.choose_move(&game) else { break; };
+            game.play(position).unwrap();
+            println!("{} plays {}", player, coordinates(position, width));
+            println!("{}\n", config.renderer.render(&game));
+            continue;
+        }
+        print!("{} to move: ", player);
+        io::stdout().flush().unwrap();
+        let Some(Ok(line)) = lines.next() else { break; };
+        match parse_command(&line, width, height) {
+            Ok(Command::Move(position)) => match game.play(position) {
+                Ok(()) => println!("{}\n", config.renderer.render(&game)),
+                Err(error) => println!("{}", error),
+            },
+            Ok(Command::Undo) => {
+                let human = |mover: Player| config.ai != Some(mover);
+                if !game.moves.iter().any(|(mover, _)| human(*mover)) {
+                    println!("there's nothing to undo");
+                    continue;
+                }
+                // against the AI its reply is taken back too, so it's the human's turn again
+                while game.undo().is_some_and(|(mover, _)| !human(mover)) {}
+                println!("{}\n", config.renderer.render(&game));
+            }
+            Ok(Command::Help) => println!("{}", HELP),
+            Ok(Command::Quit) => break,
+            Err(error) => println!("{}", error),
+        }
+    }
+    println!("{}", summary(&game));
+}
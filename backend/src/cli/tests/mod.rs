@@ -0,0 +1,38 @@
+use game::{render::{RenderStyle, Renderer}, Game, Player};
+use crate::{coordinates, parse_command, summary, Command, Config};
+
+#[test]
+fn test_parse_command() {
+    assert_eq!(parse_command("5", 3, 3), Ok(Command::Move(4)));
+    assert_eq!(parse_command(" b2\n", 3, 3), Ok(Command::Move(4)));
+    assert_eq!(parse_command("C1", 3, 3), Ok(Command::Move(2)));
+    assert_eq!(parse_command("d3", 4, 3), Ok(Command::Move(11)));
+    assert_eq!(parse_command("undo", 3, 3), Ok(Command::Undo));
+    assert_eq!(parse_command("q", 3, 3), Ok(Command::Quit));
+    assert!(parse_command("0", 3, 3).is_err());
+    assert!(parse_command("10", 3, 3).is_err());
+    assert!(parse_command("d1", 3, 3).is_err());
+    assert!(parse_command("a4", 3, 3).is_err());
+    assert!(parse_command("move", 3, 3).is_err());
+}
+
+#[test]
+fn test_parse_config() {
+    let args: Vec<String> = ["--ai", "O", "--first", "o", "--board", "4x4x3", "--style", "unicode"].map(str::to_owned).to_vec();
+    let renderer = Renderer::new(RenderStyle::Unicode);
+    assert_eq!(Config::parse(&args), Ok(Config { ai: Some(Player::O), first: Player::O, board: (4, 4, 3), renderer }));
+    assert_eq!(Config::parse(&[]), Ok(Config::default()));
+    assert!(Config::parse(&["--ai".to_owned(), "z".to_owned()]).is_err());
+}
+
+#[test]
+fn test_summary() {
+    let mut game = Game::new(Player::X);
+    assert_eq!(coordinates(5, 3), "c2");
+    for position in [0, 3, 1, 4, 2] {
+        game.play(position).unwrap();
+    }
+    assert_eq!(summary(&game), "X wins after 5 moves\nX a1, O a2, X b1, O b2, X c1");
+    game.undo();
+    assert!(summary(&game).starts_with("Game abandoned after 4 moves"));
+}
@@ -0,0 +1,76 @@
+use std::{env, process};
+use crossterm::event::{Event, EventStream};
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use state::ClientState;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream};
+
+#[cfg(test)]
+mod tests;
+mod state;
+mod ui;
+
+const USAGE: &str = "usage: client [--url URL] [--nickname NAME]";
+const DEFAULT_URL: &str = "ws://localhost:80/ws";
+
+#[tokio::main]
+pub async fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (mut url, mut nickname) = (DEFAULT_URL.to_owned(), None);
+    let mut options = args.iter();
+    while let Some(flag) = options.next() {
+        match (flag.as_str(), options.next()) {
+            ("--url", Some(value)) => url = value.clone(),
+            ("--nickname", Some(value)) => nickname = Some(value.clone()),
+            _ => {
+                eprintln!("{}", USAGE);
+                process::exit(2);
+            }
+        }
+    }
+    let (socket, _) = connect_async(&url).await.unwrap_or_else(|error| {
+        eprintln!("couldn't connect to {}: {}", url, error);
+        process::exit(1);
+    });
+    // the server identifies players in a lobby by their port
+    let port = match socket.get_ref() {
+        MaybeTlsStream::Plain(stream) => stream.local_addr().map_or(0, |address| address.port()),
+        _ => 0,
+    };
+    let (mut sender, mut receiver) = socket.split();
+    let mut client = ClientState::new(port);
+    let connection = json!({"type": "Connection", "data": {"nickname": nickname}});
+    if sender.send(Message::text(connection.to_string())).await.is_err() {
+        eprintln!("the server closed the connection");
+        process::exit(1);
+    }
+
+    let mut terminal = ratatui::init();
+    let mut events = EventStream::new();
+    let mut disconnected = None;
+    while !client.quit {
+        if let Err(error) = terminal.draw(|frame| ui::draw(frame, &client)) {
+            disconnected = Some(error.to_string());
+            break;
+        }
+        tokio::select! {
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => client.receive(text.as_str()),
+                Some(Ok(Message::Close(_))) | None => { disconnected = Some("the server closed the connection".to_owned()); break; }
+                Some(Err(error)) => { disconnected = Some(error.to_string()); break; }
+                Some(Ok(_)) => {}
+            },
+            event = events.next() => {
+                let Some(Ok(Event::Key(key))) = event else { continue; };
+                if let Some(message) = client.key(key) {
+                    if sender.send(Message::text(message.to_string())).await.is_err() {
+                        disconnected = Some("the server closed the connection".to_owned());
+                        break;
+                    }
+                }
+            },
+        }
+    }
+    ratatui::restore();
+    if let Some(reason) = disconnected { eprintln!("{}", reason); }
+}
@@ -0,0 +1,163 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use game::Player;
+use serde_json::{json, Value};
+
+/// How many lines of the message log are kept.
+const LOG_LENGTH: usize = 100;
+
+/// Everything the client knows about the server, built up from the messages it receives.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientState {
+    pub port: u16,                      // our local port, which is how the server tells players apart
+    pub access_token: Option<String>,
+    pub nickname: Option<String>,
+    pub lobby: Option<Value>,           // the last lobby the server sent us
+    pub symbol: Option<Player>,
+    pub board: Vec<Option<Player>>,
+    pub blocked: Vec<usize>,            // cells nobody can play in this game
+    pub width: usize,
+    pub height: usize,
+    pub cursor: usize,
+    pub hint: Option<usize>,
+    pub input: String,                  // the command being typed
+    pub log: Vec<String>,
+    pub quit: bool,
+}
+
+impl ClientState {
+    pub fn new(port: u16) -> Self {
+        ClientState { port, width: 3, height: 3, board: vec![None; 9], ..Default::default() }
+    }
+    pub fn log(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > LOG_LENGTH { self.log.remove(0); }
+    }
+    pub fn in_game(&self) -> bool {
+        self.lobby.as_ref().is_some_and(|lobby| lobby["game_id"].is_string() && lobby["outcome"].is_null())
+    }
+    /// Updates the state from a message sent by the server.
+    pub fn receive(&mut self, text: &str) {
+        let Ok(message) = serde_json::from_str::<Value>(text) else {
+            self.log(format!("unreadable message: {}", text));
+            return;
+        };
+        let data = &message["data"];
+        match message["type"].as_str() {
+            Some("Session") => {
+                self.access_token = data["access_token"].as_str().map(str::to_owned);
+                self.nickname = data["nickname"].as_str().map(str::to_owned);
+                self.log(format!("connected as {}", self.nickname.as_deref().unwrap_or("anonymous")));
+            }
+            Some("Lobby") | Some("PlayerJoined") | Some("PlayerLeft") => self.set_lobby(data),
+            Some("StartGame") => {
+                self.set_lobby(data);
+                self.board = vec![None; self.width * self.height];
+                self.blocked = serde_json::from_value(data["setup"]["blocked"].clone()).unwrap_or_default();
+                // marks placed before the first move
+                let marks: Vec<(usize, Player)> = serde_json::from_value(data["setup"]["marks"].clone()).unwrap_or_default();
+                for (cell, player) in marks {
+                    if let Some(slot) = self.board.get_mut(cell) { *slot = Some(player); }
+                }
+                self.hint = None;
+                self.log(match self.symbol {
+                    Some(symbol) => format!("game started, you play {}", symbol),
+                    None => "game started".to_owned(),
+                });
+            }
+            Some("Move") => {
+                if let Ok(board) = serde_json::from_value::<Vec<Option<Player>>>(data.clone()) {
+                    if board.len() == self.width * self.height { self.board = board; }
+                }
+                self.hint = None;
+            }
+            Some("GameOver") | Some("SeriesOver") => {
+                self.set_lobby(data);
+                let winner = data["outcome"]["Win"].as_str()
+                    .or_else(|| data["outcome"]["Forfeit"].as_str().map(|loser| if loser == "X" { "O" } else { "X" }));
+                self.log(match winner {
+                    Some(winner) => format!("game over, {} wins", winner),
+                    None => "game over, it's a draw".to_owned(),
+                });
+            }
+            Some("LeaveLobby") => {
+                self.lobby = None;
+                self.symbol = None;
+                self.log("left the lobby");
+            }
+            Some("Hint") => {
+                self.hint = data["best"].as_u64().map(|best| best as usize);
+                if let Some(hint) = self.hint { self.log(format!("hint: cell {}", hint + 1)); }
+            }
+            Some("Error") => self.log(format!("error: {}", data.as_str().unwrap_or_default())),
+            Some("OnHover") | Some("GameReview") | None => {}
+            Some(kind) => self.log(format!("{}: {}", kind, data)),
+        }
+    }
+    fn set_lobby(&mut self, lobby: &Value) {
+        if !lobby.is_object() { return; }
+        let joined = self.lobby.as_ref().map(|current| &current["code"]) != Some(&lobby["code"]);
+        self.symbol = lobby["players"].as_array().into_iter().flatten()
+            .find(|player| player["port"].as_u64() == Some(self.port as u64))
+            .and_then(|player| serde_json::from_value(player["symbol"].clone()).ok());
+        let board = &lobby["settings"]["board"];
+        if let (Some(width), Some(height)) = (board["width"].as_u64(), board["height"].as_u64()) {
+            let (width, height) = (width as usize, height as usize);
+            if (width, height) != (self.width, self.height) {
+                (self.width, self.height, self.cursor) = (width, height, 0);
+                self.board = vec![None; width * height];
+            }
+        }
+        if joined { self.log(format!("in lobby {}", lobby["code"].as_str().unwrap_or_default())); }
+        self.lobby = Some(lobby.clone());
+    }
+    /// Turns a typed command into the message to send, if there is one.
+    pub fn command(&mut self, command: &str) -> Result<Option<Value>, String> {
+        let mut words = command.split_whitespace();
+        let message = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => return Ok(None),
+            (Some("quit" | "q"), _, _) => { self.quit = true; return Ok(None); }
+            (Some("create"), _, _) => json!({"type": "CreateLobby"}),
+            (Some("join"), Some(code), password) => json!({"type": "JoinLobby", "data": {"code": code, "password": password}}),
+            (Some("leave"), _, _) => json!({"type": "LeaveLobby"}),
+            (Some("start"), _, _) => json!({"type": "StartGame"}),
+            (Some("nick"), Some(nickname), _) => json!({"type": "Nickname", "data": {"nickname": nickname}}),
+            (Some("hint"), _, _) => json!({"type": "RequestHint"}),
+            (Some("password"), password, _) => json!({"type": "SetPassword", "data": {"password": password}}),
+            (Some("invite"), _, _) => json!({"type": "CreateInvite", "data": {}}),
+            (Some(cell), None, _) if cell.parse::<usize>().is_ok() => {
+                let cell: usize = cell.parse().unwrap();
+                if !(1..=self.board.len()).contains(&cell) { return Err(format!("cells are numbered 1 to {}", self.board.len())); }
+                json!({"type": "Move", "data": {"position": cell - 1}})
+            }
+            (Some(word), _, _) => return Err(format!("unknown command {}", word)),
+        };
+        Ok(Some(message))
+    }
+    /// Handles a key press: arrows move the cursor, enter plays at it or sends the typed command.
+    pub fn key(&mut self, key: KeyEvent) -> Option<Value> {
+        if key.kind != KeyEventKind::Press { return None; }
+        let (row, column) = (self.cursor / self.width, self.cursor % self.width);
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => { self.input.pop(); }
+            KeyCode::Esc => self.input.clear(),
+            KeyCode::Up => self.cursor = row.saturating_sub(1) * self.width + column,
+            KeyCode::Down => self.cursor = (row + 1).min(self.height - 1) * self.width + column,
+            KeyCode::Left => self.cursor = row * self.width + column.saturating_sub(1),
+            KeyCode::Right => self.cursor = row * self.width + (column + 1).min(self.width - 1),
+            KeyCode::Enter if self.input.is_empty() && self.in_game() => {
+                return Some(json!({"type": "Move", "data": {"position": self.cursor}}));
+            }
+            KeyCode::Enter => {
+                let input = std::mem::take(&mut self.input);
+                match self.command(&input) {
+                    Ok(message) => return message,
+                    Err(error) => self.log(error),
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,77 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use game::Player;
+use serde_json::json;
+use crate::state::ClientState;
+
+fn lobby(port: u16, game_id: Option<&str>) -> serde_json::Value {
+    json!({
+        "code": "1234",
+        "players": [{"port": port, "symbol": "O"}, {"port": 9999, "symbol": "X"}],
+        "private": false,
+        "game_id": game_id,
+        "outcome": null,
+        "settings": {"board": {"width": 4, "height": 4, "win_length": 3}}
+    })
+}
+
+fn press(client: &mut ClientState, code: KeyCode) -> Option<serde_json::Value> {
+    client.key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[test]
+fn test_receive() {
+    let mut client = ClientState::new(1111);
+    client.receive(&json!({"type": "Session", "data": {"access_token": "abc", "nickname": "host"}}).to_string());
+    assert_eq!(client.access_token.as_deref(), Some("abc"));
+    client.receive(&json!({"type": "StartGame", "data": lobby(1111, Some("game"))}).to_string());
+    assert_eq!(client.symbol, Some(Player::O));
+    assert_eq!((client.width, client.height, client.board.len()), (4, 4, 16));
+    assert!(client.in_game());
+
+    let mut board = vec![None; 16];
+    board[5] = Some(Player::X);
+    client.receive(&json!({"type": "Move", "data": board}).to_string());
+    assert_eq!(client.board[5], Some(Player::X));
+    client.receive(&json!({"type": "Error", "data": "not this player's turn"}).to_string());
+    assert_eq!(client.log.last().unwrap(), "error: not this player's turn");
+    // a game started from a prepared position shows its blocked cells and marks straight away
+    let mut prepared = lobby(1111, Some("next"));
+    prepared["setup"] = json!({"blocked": [0, 15], "marks": [[5, "O"]]});
+    client.receive(&json!({"type": "StartGame", "data": prepared}).to_string());
+    assert_eq!((client.blocked.clone(), client.board[5]), (vec![0, 15], Some(Player::O)));
+    client.receive(&json!({"type": "LeaveLobby", "data": {}}).to_string());
+    assert_eq!((client.lobby.as_ref(), client.symbol), (None, None));
+}
+
+#[test]
+fn test_commands() {
+    let mut client = ClientState::new(1111);
+    assert_eq!(client.command("create"), Ok(Some(json!({"type": "CreateLobby"}))));
+    assert_eq!(client.command("join 1234 secret"),
+        Ok(Some(json!({"type": "JoinLobby", "data": {"code": "1234", "password": "secret"}}))));
+    assert_eq!(client.command("join 1234"), Ok(Some(json!({"type": "JoinLobby", "data": {"code": "1234", "password": null}}))));
+    assert_eq!(client.command("9"), Ok(Some(json!({"type": "Move", "data": {"position": 8}}))));
+    assert!(client.command("10").is_err());
+    assert!(client.command("dance").is_err());
+    assert_eq!(client.command("quit"), Ok(None));
+    assert!(client.quit);
+}
+
+#[test]
+fn test_keys() {
+    let mut client = ClientState::new(1111);
+    // enter on an empty line only plays once a game is on
+    assert_eq!(press(&mut client, KeyCode::Enter), None);
+    client.receive(&json!({"type": "StartGame", "data": lobby(1111, Some("game"))}).to_string());
+    press(&mut client, KeyCode::Right);
+    press(&mut client, KeyCode::Down);
+    press(&mut client, KeyCode::Up);
+    press(&mut client, KeyCode::Up);
+    assert_eq!(client.cursor, 1);
+    assert_eq!(press(&mut client, KeyCode::Enter), Some(json!({"type": "Move", "data": {"position": 1}})));
+    for c in "start".chars() {
+        press(&mut client, KeyCode::Char(c));
+    }
+    assert_eq!(press(&mut client, KeyCode::Enter), Some(json!({"type": "StartGame"})));
+    assert!(client.input.is_empty());
+}
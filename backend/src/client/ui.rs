@@ -0,0 +1,62 @@
+use game::Player;
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use crate::state::ClientState;
+
+const COMMANDS: &str = "create | join CODE [PASSWORD] | start | leave | nick NAME | hint | password [PASSWORD] | invite | CELL | quit";
+
+fn status(client: &ClientState) -> Line<'static> {
+    let Some(lobby) = &client.lobby else { return Line::from("not in a lobby, type create or join CODE"); };
+    let seats = lobby["players"].as_array().map_or(2, Vec::len);
+    let players = lobby["players"].as_array().map_or(0, |players| players.iter().filter(|player| player.is_object()).count());
+    let symbol = client.symbol.map_or("spectating".to_owned(), |symbol| format!("you play {}", symbol));
+    let state = if client.in_game() { "in game" } else { "waiting" };
+    Line::from(format!("lobby {}  {}/{} players  {}  {}", lobby["code"].as_str().unwrap_or_default(), players, seats, symbol, state))
+}
+
+fn board(client: &ClientState) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for row in 0..client.height {
+        let mut spans = Vec::new();
+        for column in 0..client.width {
+            let position = row * client.width + column;
+            let (label, color) = match client.board[position] {
+                Some(Player::X) => ("X".to_owned(), Color::Red),
+                Some(Player::O) => ("O".to_owned(), Color::Blue),
+                Some(player) => (player.to_string(), Color::Yellow),
+                None if client.blocked.contains(&position) => ("#".to_owned(), Color::DarkGray),
+                None if client.hint == Some(position) => ("*".to_owned(), Color::Green),
+                None => (" ".to_owned(), Color::Reset),
+            };
+            let mut style = Style::default().fg(color);
+            if position == client.cursor { style = style.add_modifier(Modifier::REVERSED); }
+            spans.push(Span::styled(format!(" {} ", label), style));
+            if column + 1 < client.width { spans.push(Span::raw("│")); }
+        }
+        lines.push(Line::from(spans));
+        if row + 1 < client.height {
+            lines.push(Line::from(vec!["───"; client.width].join("┼")));
+        }
+    }
+    lines
+}
+
+pub fn draw(frame: &mut Frame, client: &ClientState) {
+    let [status_area, board_area, log_area, input_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(client.height as u16 * 2 + 1),
+        Constraint::Min(3),
+        Constraint::Length(3),
+    ]).areas(frame.area());
+    frame.render_widget(Paragraph::new(status(client)).block(Block::default().borders(Borders::ALL).title("tictactoe")), status_area);
+    frame.render_widget(Paragraph::new(board(client)).block(Block::default().borders(Borders::ALL).title("board")), board_area);
+    let visible = log_area.height.saturating_sub(2) as usize;
+    let log: Vec<Line> = client.log.iter().skip(client.log.len().saturating_sub(visible)).map(|line| Line::from(line.as_str())).collect();
+    frame.render_widget(Paragraph::new(log).block(Block::default().borders(Borders::ALL).title("messages")), log_area);
+    frame.render_widget(Paragraph::new(format!("> {}", client.input)).block(Block::default().borders(Borders::ALL).title(COMMANDS)), input_area);
+}
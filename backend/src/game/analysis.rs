@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::{Game, Outcome, Player, Variant};
+
+/// The game-theoretic value of a move or position for the player making it,
+/// with the number of moves (plies) until the game is decided.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum Evaluation {
+    Win(u8),
+    Draw,
+    Loss(u8),
+}
+
+impl Evaluation {
+    /// A score that orders evaluations from best to worst: quicker wins first, slower losses last.
+    pub fn score(&self) -> i32 {
+        match self {
+            Evaluation::Win(distance) => 100 - *distance as i32,
+            Evaluation::Draw => 0,
+            Evaluation::Loss(distance) => -100 + *distance as i32,
+        }
+    }
+    /// The same result seen from the other player's side, one move further away.
+    pub(crate) fn flip(&self) -> Evaluation {
+        match self {
+            Evaluation::Win(distance) => Evaluation::Loss(distance + 1),
+            Evaluation::Draw => Evaluation::Draw,
+            Evaluation::Loss(distance) => Evaluation::Win(distance + 1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct MoveAnalysis {
+    pub position: usize,
+    pub value: Evaluation,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Analysis {
+    pub player: Option<Player>,     // the player to move, None when the game is over
+    pub value: Option<Evaluation>,  // the value of the position for the player to move
+    pub moves: Vec<MoveAnalysis>,
+    pub best: Option<usize>,        // the recommended move
+}
+
+/// Solves the position, returning every legal move with its value for the player to move.
+pub fn analyze(game: &Game) -> Analysis {
+    let mut solver = Solver::default();
+    let player = game.current_player;
+    let moves: Vec<MoveAnalysis> = match player {
+        Some(player) => legal_moves(game).into_iter()
+            .map(|position| MoveAnalysis { position, value: solver.evaluate_move(game, &player, position) })
+            .collect(),
+        None => Vec::new(),
+    };
+    let best = moves.iter().max_by_key(|analysis| analysis.value.score()).copied();
+    Analysis { player, value: best.map(|best| best.value), moves, best: best.map(|best| best.position) }
+}
+
+/// The recommended move for the player to move, if the game isn't over.
+pub fn best_move(game: &Game) -> Option<usize> {
+    analyze(game).best
+}
+
+pub fn legal_moves(game: &Game) -> Vec<usize> {
+    game.legal_moves()
+}
+
+/// A position as the solver remembers it: the rules, the board and the player to move.
+type Key = (Variant, Vec<Option<Player>>, Option<Player>);
+
+/// Negamax search over the game tree, remembering positions it has already solved.
+#[derive(Default)]
+pub struct Solver {
+    solved: HashMap<Key, Evaluation>,
+}
+
+impl Solver {
+    /// The value of a position for the player to move.
+    pub fn evaluate(&mut self, game: &Game) -> Evaluation {
+        let key = (game.variant, game.board.clone(), game.current_player);
+        if let Some(value) = self.solved.get(&key) { return *value; }
+        let value = match (game.outcome, game.current_player) {
+            (Some(Outcome::Draw), _) | (None, None) => Evaluation::Draw,
+            // the game ended on the previous move, which usually means the player who made it won,
+            // but not in misère games
+            (Some(outcome), _) => match (outcome.winner(), game.moves.last()) {
+                (Some(winner), Some((last, _))) if winner != *last => Evaluation::Win(0),
+                _ => Evaluation::Loss(0),
+            },
+            (None, Some(player)) => legal_moves(game).into_iter()
+                .map(|position| self.evaluate_move(game, &player, position))
+                .max_by_key(|value| value.score())
+                .unwrap_or(Evaluation::Draw),
+        };
+        self.solved.insert(key, value);
+        value
+    }
+    /// The value of making a move for the player making it.
+    pub fn evaluate_move(&mut self, game: &Game, player: &Player, position: usize) -> Evaluation {
+        let mut next = game.clone();
+        if game.current_player != Some(*player) || next.play(position).is_err() { return Evaluation::Loss(0); }
+        self.evaluate(&next).flip()
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+use crate::{Game, Player, Variant, DIRECTIONS};
+
+/// The most cells a bitboard can hold, one bit per cell.
+pub const MAX_CELLS: usize = 64;
+
+/// Every winning line on a board of a given size as a bitmask, along with the lines through each cell.
+#[derive(Debug, PartialEq)]
+pub struct WinMasks {
+    pub lines: Vec<u64>,
+    pub through: Vec<Vec<u64>>, // indexed by cell
+}
+
+impl WinMasks {
+    pub fn new(width: usize, height: usize, win_length: usize) -> Self {
+        let mut lines = Vec::new();
+        for row in 0..height as isize {
+            for column in 0..width as isize {
+                for (row_step, column_step) in DIRECTIONS {
+                    let cells: Vec<(isize, isize)> = (0..win_length as isize)
+                        .map(|i| (row + i * row_step, column + i * column_step))
+                        .collect();
+                    let fits = cells.iter().all(|(r, c)| *r >= 0 && *c >= 0 && *r < height as isize && *c < width as isize);
+                    if fits {
+                        lines.push(cells.iter().fold(0, |mask, (r, c)| mask | 1 << (*r as usize * width + *c as usize)));
+                    }
+                }
+            }
+        }
+        let through = (0..width * height)
+            .map(|cell| lines.iter().copied().filter(|line| line & 1 << cell != 0).collect())
+            .collect();
+        WinMasks { lines, through }
+    }
+}
+
+/// A board stored as one bitmask per player, for engines that make and unmake millions of moves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bitboard {
+    pub width: usize,
+    pub height: usize,
+    pub masks: [u64; 2],            // cells held by X and by O
+    pub to_move: Player,
+    pub win_masks: Arc<WinMasks>,   // shared between copies, the masks only depend on the board size
+    winner: Option<Player>,         // kept up to date by each move, so finding it never scans the whole board
+    won_at: Option<usize>,          // the move that completed the winning line, undone by unmaking it
+}
+
+fn index(player: Player) -> usize {
+    player.index()
+}
+
+impl Bitboard {
+    pub fn new(width: usize, height: usize, win_length: usize, to_move: Player) -> Result<Self, &'static str> {
+        if width * height > MAX_CELLS { return Err("The board is too big for a bitboard"); }
+        if width == 0 || height == 0 || win_length == 0 || win_length > width.max(height) {
+            return Err("The winning line doesn't fit on the board");
+        }
+        let win_masks = Arc::new(WinMasks::new(width, height, win_length));
+        Ok(Bitboard { width, height, masks: [0, 0], to_move, win_masks, winner: None, won_at: None })
+    }
+    /// Converts the array form of a board, `to_move` being the player whose turn it is.
+    pub fn from_board(board: &[Option<Player>], width: usize, height: usize, win_length: usize, to_move: Player) -> Result<Self, &'static str> {
+        if board.len() != width * height { return Err("The board doesn't match its size"); }
+        // there's only a mask for X and one for O
+        if board.iter().flatten().chain([&to_move]).any(|player| player.index() > 1) {
+            return Err("Only X and O fit in a bitboard");
+        }
+        let mut bitboard = Bitboard::new(width, height, win_length, to_move)?;
+        for (cell, player) in board.iter().enumerate() {
+            if let Some(player) = player {
+                bitboard.masks[index(*player)] |= 1 << cell;
+            }
+        }
+        bitboard.winner = bitboard.find_winner();
+        Ok(bitboard)
+    }
+    pub fn from_game(game: &Game) -> Result<Self, &'static str> {
+        if game.variant != Variant::Standard || game.players != 2 { return Err("Only standard two-player games fit in a bitboard"); }
+        if !game.setup.blocked.is_empty() { return Err("Blocked cells don't fit in a bitboard"); }
+        // a finished game has nobody to move, so carry on with whoever would have been next
+        let to_move = game.current_player
+            .or_else(|| game.moves.last().map(|(player, _)| player.opponent()))
+            .unwrap_or(Player::X);
+        Bitboard::from_board(&game.board, game.width, game.height, game.win_length, to_move)
+    }
+    pub fn to_board(&self) -> Vec<Option<Player>> {
+        (0..self.width * self.height).map(|cell| self.get(cell)).collect()
+    }
+    pub fn get(&self, cell: usize) -> Option<Player> {
+        if self.masks[0] & 1 << cell != 0 { Some(Player::X) }
+        else if self.masks[1] & 1 << cell != 0 { Some(Player::O) }
+        else { None }
+    }
+    pub fn full_mask(&self) -> u64 {
+        let cells = self.width * self.height;
+        if cells == MAX_CELLS { u64::MAX } else { (1 << cells) - 1 }
+    }
+    pub fn occupied(&self) -> u64 {
+        self.masks[0] | self.masks[1]
+    }
+    pub fn is_full(&self) -> bool {
+        self.occupied() == self.full_mask()
+    }
+    /// Empty cells as a bitmask. Use `moves` to iterate over them.
+    pub fn legal_moves(&self) -> u64 {
+        if self.winner.is_some() { return 0; }
+        !self.occupied() & self.full_mask()
+    }
+    pub fn moves(&self) -> impl Iterator<Item = usize> {
+        let mut remaining = self.legal_moves();
+        std::iter::from_fn(move || {
+            if remaining == 0 { return None; }
+            let cell = remaining.trailing_zeros() as usize;
+            remaining &= remaining - 1;
+            Some(cell)
+        })
+    }
+    /// Places the mark of the player to move. The cell must be empty, this is not checked.
+    pub fn make_move(&mut self, cell: usize) {
+        self.masks[index(self.to_move)] |= 1 << cell;
+        if self.winner.is_none() && self.wins_at(cell) { (self.winner, self.won_at) = (Some(self.to_move), Some(cell)); }
+        self.to_move = self.to_move.opponent();
+    }
+    /// Takes back a move made with `make_move`.
+    pub fn unmake_move(&mut self, cell: usize) {
+        self.to_move = self.to_move.opponent();
+        self.masks[index(self.to_move)] &= !(1 << cell);
+        if self.won_at == Some(cell) { (self.winner, self.won_at) = (None, None); }
+    }
+    /// Whether the mark on `cell` is part of a winning line, only the lines through it are checked.
+    pub fn wins_at(&self, cell: usize) -> bool {
+        let Some(player) = self.get(cell) else { return false; };
+        let mask = self.masks[index(player)];
+        self.win_masks.through[cell].iter().any(|line| mask & line == *line)
+    }
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+    /// Looks through every winning line, for boards that weren't built up move by move.
+    fn find_winner(&self) -> Option<Player> {
+        [Player::X, Player::O].into_iter().find(|player| {
+            let mask = self.masks[index(*player)];
+            self.win_masks.lines.iter().any(|line| mask & line == *line)
+        })
+    }
+}
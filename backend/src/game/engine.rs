@@ -0,0 +1,52 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use crate::{analysis::Solver, Game};
+
+/// Something that picks moves. Engines are interchangeable so callers can choose one per game.
+pub trait Engine {
+    fn name(&self) -> &'static str;
+    /// The move the engine would make for the player to move, None when the game is over.
+    fn choose_move(&mut self, game: &Game) -> Option<usize>;
+}
+
+/// Exhaustive negamax search. Plays perfectly, but only finishes in time on small boards.
+#[derive(Default)]
+pub struct MinimaxEngine {
+    solver: Solver,
+}
+
+impl MinimaxEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Engine for MinimaxEngine {
+    fn name(&self) -> &'static str {
+        "minimax"
+    }
+    fn choose_move(&mut self, game: &Game) -> Option<usize> {
+        let player = game.current_player?;
+        game.legal_moves().into_iter()
+            .max_by_key(|position| self.solver.evaluate_move(game, &player, *position).score())
+    }
+}
+
+/// Plays a uniformly random legal move, a baseline for comparing other engines.
+pub struct RandomEngine {
+    rng: StdRng,
+}
+
+impl RandomEngine {
+    pub fn new(seed: u64) -> Self {
+        RandomEngine { rng: StdRng::seed_from_u64(seed) }
+    }
+}
+
+impl Engine for RandomEngine {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+    fn choose_move(&mut self, game: &Game) -> Option<usize> {
+        game.legal_moves().choose(&mut self.rng).copied()
+    }
+}
@@ -0,0 +1,29 @@
+//! Gravity, or Connect Four: marks fall to the lowest empty cell of the column they're dropped in.
+//! Moves are still board positions, but the only legal one in each column is its landing cell.
+use crate::Game;
+
+impl Game {
+    /// The cell a mark dropped in `column` lands on, None when the column is full or doesn't exist.
+    pub fn landing(&self, column: usize) -> Option<usize> {
+        if column >= self.width { return None; }
+        (0..self.height).rev()
+            .map(|row| row * self.width + column)
+            .find(|position| self.board[*position].is_none())
+    }
+    /// Drops the current player's mark in `column`, returning where it landed.
+    pub fn drop(&mut self, column: usize) -> Result<usize, &'static str> {
+        if column >= self.width { return Err("Column is out of bounds"); }
+        let position = self.landing(column).ok_or("That column is full")?;
+        self.play(position)?;
+        Ok(position)
+    }
+    pub(crate) fn check_gravity(&self, position: usize) -> Result<(), &'static str> {
+        if self.landing(position % self.width) != Some(position) {
+            return Err("Marks fall to the lowest empty cell of their column");
+        }
+        Ok(())
+    }
+    pub(crate) fn gravity_moves(&self) -> Vec<usize> {
+        (0..self.width).filter_map(|column| self.landing(column)).collect()
+    }
+}
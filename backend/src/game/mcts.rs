@@ -0,0 +1,120 @@
+use std::time::{Duration, Instant};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use crate::{engine::Engine, Game, Player};
+
+/// How long the search runs for. When both limits are set, whichever is reached first stops it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MctsConfig {
+    pub iterations: Option<u32>,
+    pub time_limit: Option<Duration>,
+    pub exploration: f64,   // the UCT exploration constant
+    pub seed: u64,          // playouts are random, a fixed seed makes searches reproducible
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        MctsConfig { iterations: Some(10_000), time_limit: None, exploration: std::f64::consts::SQRT_2, seed: 0 }
+    }
+}
+
+struct Node {
+    position: Option<usize>,    // the move that led here, None for the root
+    player: Option<Player>,     // who made that move
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<usize>,
+    visits: u32,
+    reward: f64,                // total reward from the point of view of `player`
+}
+
+/// Monte Carlo tree search with UCT selection and random playouts.
+pub struct MctsEngine {
+    pub config: MctsConfig,
+    rng: StdRng,
+}
+
+impl MctsEngine {
+    pub fn new(config: MctsConfig) -> Self {
+        MctsEngine { config, rng: StdRng::seed_from_u64(config.seed) }
+    }
+    /// Runs the search and returns each move from the root with its visit count.
+    pub fn search(&mut self, game: &Game) -> Vec<(usize, u32)> {
+        if game.is_over() { return Vec::new(); }
+        let mut nodes = vec![Node {
+            position: None, player: None, parent: None, children: Vec::new(),
+            untried: game.legal_moves(), visits: 0, reward: 0.0
+        }];
+        let started = Instant::now();
+        let mut iterations = 0;
+        while self.config.iterations.is_none_or(|limit| iterations < limit)
+            && self.config.time_limit.is_none_or(|limit| started.elapsed() < limit) {
+            self.iterate(game, &mut nodes);
+            iterations += 1;
+            // without any budget at all, a single iteration is all we can justify
+            if self.config.iterations.is_none() && self.config.time_limit.is_none() { break; }
+        }
+        nodes[0].children.iter()
+            .map(|child| (nodes[*child].position.unwrap(), nodes[*child].visits))
+            .collect()
+    }
+    fn iterate(&mut self, game: &Game, nodes: &mut Vec<Node>) {
+        let mut state = game.clone();
+        let mut current = 0;
+        // selection: follow the most promising children until a node still has moves to try
+        while nodes[current].untried.is_empty() && !nodes[current].children.is_empty() {
+            current = self.select(nodes, current);
+            let _ = state.play(nodes[current].position.unwrap());
+        }
+        // expansion
+        if !nodes[current].untried.is_empty() {
+            let index = self.rng.gen_range(0..nodes[current].untried.len());
+            let position = nodes[current].untried.swap_remove(index);
+            let player = state.current_player;
+            let _ = state.play(position);
+            nodes.push(Node {
+                position: Some(position), player, parent: Some(current), children: Vec::new(),
+                untried: state.legal_moves(), visits: 0, reward: 0.0
+            });
+            let child = nodes.len() - 1;
+            nodes[current].children.push(child);
+            current = child;
+        }
+        // simulation
+        while let Some(position) = state.legal_moves().choose(&mut self.rng).copied() {
+            let _ = state.play(position);
+        }
+        let winner = state.outcome.and_then(|outcome| outcome.winner());
+        // backpropagation
+        let mut node = Some(current);
+        while let Some(index) = node {
+            nodes[index].visits += 1;
+            nodes[index].reward += match (winner, nodes[index].player) {
+                (None, _) => 0.5,
+                (Some(winner), Some(player)) if winner == player => 1.0,
+                _ => 0.0,
+            };
+            node = nodes[index].parent;
+        }
+    }
+    fn select(&self, nodes: &[Node], parent: usize) -> usize {
+        let parent_visits = (nodes[parent].visits.max(1) as f64).ln();
+        *nodes[parent].children.iter()
+            .max_by(|a, b| {
+                let uct = |node: &Node| node.reward / node.visits as f64
+                    + self.config.exploration * (parent_visits / node.visits as f64).sqrt();
+                uct(&nodes[**a]).total_cmp(&uct(&nodes[**b]))
+            })
+            .unwrap()
+    }
+}
+
+impl Engine for MctsEngine {
+    fn name(&self) -> &'static str {
+        "mcts"
+    }
+    fn choose_move(&mut self, game: &Game) -> Option<usize> {
+        self.search(game).into_iter()
+            .max_by_key(|(_, visits)| *visits)
+            .map(|(position, _)| position)
+    }
+}
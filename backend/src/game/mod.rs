@@ -0,0 +1,475 @@
+use std::fmt::Debug;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+pub mod analysis;
+pub mod bitboard;
+pub mod engine;
+pub mod gravity;
+pub mod mcts;
+pub mod morris;
+pub mod notakto;
+pub mod order_chaos;
+pub mod quantum;
+pub mod render;
+pub mod setup;
+pub mod symmetry;
+pub mod tablebase;
+pub mod ultimate;
+pub mod zobrist;
+
+/// A player, numbered in turn order from 0. Two-player games are between X and O.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Player(pub u8);
+
+/// The most players a game can have.
+pub const MAX_PLAYERS: usize = 4;
+/// How each player is written, by number, on boards and on the wire.
+pub const SYMBOLS: [&str; MAX_PLAYERS] = ["X", "O", "Y", "Z"];
+
+impl Player {
+    pub const X: Player = Player(0);
+    pub const O: Player = Player(1);
+    /// The other player of a two-player game. Use `Game::next_player` when there may be more players.
+    pub fn opponent(&self) -> Player {
+        debug_assert!(self.0 < 2, "only X and O have a single opponent");
+        Player(self.0 ^ 1)
+    }
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+    /// The first `count` players, in turn order.
+    pub fn all(count: usize) -> impl Iterator<Item = Player> {
+        (0..count.min(MAX_PLAYERS) as u8).map(Player)
+    }
+}
+
+impl Serialize for Player {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.serialize_str(SYMBOLS.get(self.index()).ok_or(serde::ser::Error::custom("unknown player"))?)
+    }
+}
+
+impl<'de> Deserialize<'de> for Player {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let symbol = String::deserialize(deserializer)?;
+        let index = SYMBOLS.iter().position(|known| *known == symbol)
+            .ok_or_else(|| de::Error::unknown_variant(&symbol, &SYMBOLS))?;
+        Ok(Player(index as u8))
+    }
+}
+
+/// How a finished game ended. `Forfeit` holds the player who gave up a two-player game.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Outcome { Win(Player), Draw, Forfeit(Player) }
+
+impl Outcome {
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            Outcome::Win(player) => Some(*player),
+            Outcome::Forfeit(player) => Some(player.opponent()),
+            Outcome::Draw => None,
+        }
+    }
+}
+
+/// The rules a game is played by.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Variant {
+    #[default]
+    Standard,
+    Ultimate,   // nine sub-boards in a grid, see `ultimate`
+    Misere,     // completing a line loses
+    Gravity,    // marks fall to the bottom of their column, see `gravity`
+    Morris { adjacent: bool },  // a few pieces each that move once placed, see `morris`
+    Wild,       // either player may place either symbol, and completing any line wins
+    OrderChaos, // X plays Order and O plays Chaos, see `order_chaos`
+    Notakto,    // everyone places X on 3x3 boards side by side, see `notakto`
+    Quantum,    // marks in two cells at once until they collapse, see `quantum`
+    Elimination, // completing a line knocks its player out, the last one left wins
+}
+
+impl Variant {
+    /// Whether a position can come up again, which the solver can't search.
+    pub fn can_repeat(&self) -> bool {
+        matches!(self, Variant::Morris { .. })
+    }
+    /// Whether the solver can search the variant. It remembers positions by their board, so they can't
+    /// repeat or depend on anything the board doesn't show, like Ultimate's forced sub-board.
+    pub fn is_searchable(&self) -> bool {
+        !self.can_repeat() && !matches!(self, Variant::Quantum | Variant::Ultimate)
+    }
+    /// Whether games can start from a prepared position. The other variants have rules about where marks
+    /// go, or keep track of more than the board shows.
+    pub fn allows_setup(&self) -> bool {
+        matches!(self, Variant::Standard | Variant::Misere | Variant::Wild | Variant::OrderChaos | Variant::Elimination)
+    }
+    /// Whether players pick which symbol to place on every move.
+    pub fn chooses_symbols(&self) -> bool {
+        matches!(self, Variant::Wild | Variant::OrderChaos)
+    }
+    /// What the player holding `player` is called, in variants where players have roles as well as symbols.
+    pub fn role(&self, player: Player) -> Option<&'static str> {
+        match (self, player) {
+            (Variant::OrderChaos, order_chaos::ORDER) => Some("Order"),
+            (Variant::OrderChaos, _) => Some("Chaos"),
+            _ => None,
+        }
+    }
+}
+
+/// A move as players describe it. Engines and the move history use the numbers `Game::encode`
+/// turns these into, where placing a mark is just its position.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Move {
+    Place(usize),
+    Slide { from: usize, to: usize },   // moves a piece already on the board
+    Mark { position: usize, symbol: Player }, // places a symbol of the mover's choosing, where the variant allows it
+    Spooky { first: usize, second: usize },   // a quantum mark in two cells at once
+    Collapse(usize),                // the cell the mark closing a quantum cycle collapses into
+}
+
+/// Directions a line can run in, as (row, column) steps: across, down and both diagonals.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+pub const MAX_BOARD_SIZE: usize = 19;
+/// How many times a position has to come up for the game to be drawn.
+pub const REPETITIONS: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct Game {
+    pub current_player: Option<Player>, // None once the game is over
+    pub board: Vec<Option<Player>>,     // cells in row-major order
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,              // how many in a row wins
+    pub outcome: Option<Outcome>,
+    pub moves: Vec<(Player, usize)>,    // every move made so far, in order
+    pub hash: u64,                      // Zobrist hash of the board and the player to move
+    pub history: Vec<u64>,              // the hash of every position so far, to spot repetitions
+    pub variant: Variant,
+    pub quantum: Option<quantum::Quantum>, // spooky marks and the like, in quantum games only
+    pub players: usize,                 // how many players take turns, in order from X
+    pub eliminated: Vec<Player>,        // players knocked out of the game, in the order they went
+    pub setup: setup::Setup,            // the position the game started from, blocked cells included
+}
+
+impl Game {
+    pub fn new(starting_player: Player) -> Self {
+        Self::with_size(3, 3, 3, starting_player).unwrap()
+    }
+    /// A game on a `width` by `height` board where `win_length` in a row wins.
+    pub fn with_size(width: usize, height: usize, win_length: usize, starting_player: Player) -> Result<Self, &'static str> {
+        Self::with_variant(Variant::Standard, width, height, win_length, starting_player)
+    }
+    pub fn with_variant(variant: Variant, width: usize, height: usize, win_length: usize, starting_player: Player) -> Result<Self, &'static str> {
+        Self::with_players(variant, width, height, win_length, 2, starting_player)
+    }
+    /// A game for `players` players, who take turns in order starting with `starting_player`.
+    pub fn with_players(variant: Variant, width: usize, height: usize, win_length: usize, players: usize, starting_player: Player) -> Result<Self, &'static str> {
+        if !(2..=MAX_PLAYERS).contains(&players) { return Err("A game has two to four players"); }
+        if starting_player.index() >= players { return Err("The starting player isn't in the game"); }
+        if players > 2 && !matches!(variant, Variant::Standard | Variant::Elimination) {
+            return Err("Only standard and elimination games can have more than two players");
+        }
+        if !(1..=MAX_BOARD_SIZE).contains(&width) || !(1..=MAX_BOARD_SIZE).contains(&height) {
+            return Err("The board size is out of bounds");
+        }
+        if win_length == 0 || win_length > width.max(height) {
+            return Err("The winning line doesn't fit on the board");
+        }
+        if variant == Variant::Ultimate && (width, height, win_length) != (ultimate::SIZE, ultimate::SIZE, 3) {
+            return Err("Ultimate tic-tac-toe is played on a 9x9 board with 3 in a row");
+        }
+        if variant == Variant::OrderChaos && (width, height, win_length) != (order_chaos::SIZE, order_chaos::SIZE, order_chaos::LINE) {
+            return Err("Order and Chaos is played on a 6x6 board with 5 in a row");
+        }
+        if variant == Variant::Quantum && (width, height, win_length) != (3, 3, 3) {
+            return Err("Quantum tic-tac-toe is played on a 3x3 board with 3 in a row");
+        }
+        if variant == Variant::Notakto && (width % 3, height, win_length) != (0, 3, 3) {
+            return Err("Notakto is played on 3x3 boards side by side");
+        }
+        if matches!(variant, Variant::Morris { .. }) && win_length * 2 >= width * height {
+            return Err("There's no room to move once every piece is placed");
+        }
+        let board: Vec<Option<Player>> = vec![None; width * height];
+        let current_player: Option<Player> = Some(starting_player);
+        let hash = zobrist::hash(&board, current_player);
+        let quantum = (variant == Variant::Quantum).then(|| quantum::Quantum::new(board.len()));
+        Ok(Game {
+            board, width, height, win_length, current_player, outcome: None, moves: Vec::new(), hash, history: vec![hash],
+            variant, quantum, players, eliminated: Vec::new(), setup: setup::Setup::default()
+        })
+    }
+    pub fn ultimate(starting_player: Player) -> Self {
+        Self::with_variant(Variant::Ultimate, ultimate::SIZE, ultimate::SIZE, 3, starting_player).unwrap()
+    }
+    pub fn winner(&self) -> Option<Player> {
+        match self.variant {
+            Variant::Ultimate => return self.meta_winner(),
+            Variant::Quantum => return self.quantum_winner(),
+            Variant::Elimination => return self.survivor(),
+            _ => {}
+        }
+        let symbol = (0..self.board.len()).find_map(|position| {
+            let symbol = self.board[position]?;
+            self.completes_line(position).then_some(symbol)
+        });
+        // in wild games the line belongs to whoever completed it, whatever its symbol, and in Notakto
+        // killing the last board loses
+        match self.variant {
+            Variant::Wild => symbol.and(self.moves.last().map(|(player, _)| *player)),
+            Variant::Notakto if (0..self.boards()).all(|board| self.is_dead(board))
+                => self.moves.last().map(|(player, _)| player.opponent()),
+            Variant::Notakto => None,
+            Variant::OrderChaos if symbol.is_some() => Some(order_chaos::ORDER),
+            Variant::OrderChaos => (0..self.board.len())
+                .all(|position| self.board[position].is_some() || self.is_blocked(position))
+                .then_some(order_chaos::CHAOS),
+            _ => symbol.map(|symbol| self.line_winner(symbol)),
+        }
+    }
+    /// Whether the mark at `position` is part of a winning line. In ultimate games and Notakto only its 3x3 board counts,
+    /// and in Order and Chaos the line has to be exactly as long as a win, six in a row doesn't count.
+    pub fn completes_line(&self, position: usize) -> bool {
+        let Some(player) = self.board[position] else { return false; };
+        let (row, column) = ((position / self.width) as isize, (position % self.width) as isize);
+        let (top, left, bottom, right) = match self.variant {
+            Variant::Ultimate | Variant::Notakto => (row / 3 * 3, column / 3 * 3, row / 3 * 3 + 3, column / 3 * 3 + 3),
+            _ => (0, 0, self.height as isize, self.width as isize),
+        };
+        let exact = self.variant == Variant::OrderChaos;
+        // looking one cell further than a win needs shows whether the line runs on
+        let reach = if exact { self.win_length } else { self.win_length - 1 } as isize;
+        DIRECTIONS.iter().any(|(row_step, column_step)| {
+            let count = |sign: isize| (1..=reach)
+                .take_while(|distance| {
+                    let (r, c) = (row + sign * distance * row_step, column + sign * distance * column_step);
+                    r >= top && c >= left && r < bottom && c < right
+                        && self.board[r as usize * self.width + c as usize] == Some(player)
+                })
+                .count();
+            let length = 1 + count(1) + count(-1);
+            if exact { length == self.win_length } else { length >= self.win_length }
+        })
+    }
+    /// Who wins when `player` completes a line.
+    fn line_winner(&self, player: Player) -> Player {
+        match self.variant {
+            Variant::Misere | Variant::Notakto => player.opponent(),
+            Variant::OrderChaos => order_chaos::ORDER,
+            _ => player,
+        }
+    }
+    /// The only player left once everyone else has been eliminated.
+    fn survivor(&self) -> Option<Player> {
+        if self.eliminated.is_empty() { return None; }
+        let mut remaining = Player::all(self.players).filter(|player| !self.eliminated.contains(player));
+        match (remaining.next(), remaining.next()) {
+            (Some(survivor), None) => Some(survivor),
+            _ => None,
+        }
+    }
+    /// The player after `player` in turn order, skipping anyone eliminated.
+    pub fn next_player(&self, player: Player) -> Player {
+        (1..=self.players)
+            .map(|step| Player(((player.index() + step) % self.players) as u8))
+            .find(|next| !self.eliminated.contains(next))
+            .unwrap_or(player)
+    }
+    pub fn is_over(&self) -> bool {
+        self.outcome.is_some()
+    }
+    /// Ends an unfinished game in favour of the other player. With more players, the one
+    /// giving up is eliminated and the rest play on.
+    pub fn forfeit(&mut self, player: &Player) {
+        if self.is_over() { return; }
+        if self.players > 2 {
+            if !self.eliminated.contains(player) { self.eliminated.push(*player); }
+            if let Some(survivor) = self.survivor() {
+                self.outcome = Some(Outcome::Win(survivor));
+                self.set_current_player(None);
+            } else if self.current_player == Some(*player) {
+                self.set_current_player(Some(self.next_player(*player)));
+            }
+            return;
+        }
+        self.outcome = Some(Outcome::Forfeit(*player));
+        self.set_current_player(None);
+    }
+    pub fn get_board(&self) -> &[Option<Player>] {
+        &self.board
+    }
+    pub fn move_player(&mut self, player: &Player, position: usize) -> Result<(), &str> {
+        if self.current_player.is_some_and(|x| x.ne(player)) {
+            return Err("Cannot move the player. It is not their turn.");
+        }
+        self.play(position)
+    }
+    /// Like `move_player`, but places `symbol` at `position`, which only wild games and Order and Chaos
+    /// allow unless it's the player's own.
+    pub fn move_symbol(&mut self, player: &Player, position: usize, symbol: Player) -> Result<(), &str> {
+        if !self.variant.chooses_symbols() {
+            if symbol != *player { return Err("Players can only place their own symbol"); }
+            return self.move_player(player, position);
+        }
+        if position >= self.board.len() { return Err("Position is out of bounds"); }
+        // moves only have room for the two symbols, anything else would be placed as X
+        if ![Player::X, Player::O].contains(&symbol) { return Err("Only X or O can be placed in this variant"); }
+        self.move_player(player, self.encode(Move::Mark { position, symbol }))
+    }
+    /// The number engines and the move history use for `mv`.
+    pub fn encode(&self, mv: Move) -> usize {
+        let cells = self.board.len();
+        match mv {
+            Move::Place(position) => position,
+            Move::Slide { from, to } => cells + from * cells + to,
+            Move::Mark { position, symbol } => cells + position * 2 + (symbol == Player::O) as usize,
+            Move::Spooky { first, second } => cells + first * cells + second,
+            Move::Collapse(cell) => cells + cells * cells + cell,
+        }
+    }
+    /// The move an encoded number stands for, None when it's out of bounds for this game.
+    pub fn decode(&self, index: usize) -> Option<Move> {
+        let cells = self.board.len();
+        match index.checked_sub(cells) {
+            None => Some(Move::Place(index)),
+            Some(slide) if matches!(self.variant, Variant::Morris { .. }) && slide < cells * cells
+                => Some(Move::Slide { from: slide / cells, to: slide % cells }),
+            Some(spooky) if self.variant == Variant::Quantum && spooky < cells * cells
+                => Some(Move::Spooky { first: spooky / cells, second: spooky % cells }),
+            Some(collapse) if self.variant == Variant::Quantum && collapse < cells * cells + cells
+                => Some(Move::Collapse(collapse - cells * cells)),
+            Some(mark) if self.variant.chooses_symbols() && mark < cells * 2
+                => Some(Move::Mark { position: mark / 2, symbol: [Player::X, Player::O][mark % 2] }),
+            Some(_) => None,
+        }
+    }
+    /// Makes an encoded move for the player to move, for callers that don't name the player.
+    /// In most variants that's placing their mark at a position.
+    pub fn play(&mut self, index: usize) -> Result<(), &'static str> {
+        let mv = self.decode(index).ok_or("Position is out of bounds")?;
+        if self.variant == Variant::Quantum { return self.play_quantum(index, mv); }
+        let position = match mv {
+            Move::Place(position) | Move::Slide { to: position, .. } | Move::Mark { position, .. } => position,
+            Move::Spooky { .. } | Move::Collapse(_) => return Err("That move is only part of quantum tic-tac-toe"),
+        };
+        if self.board[position].is_some() {
+            return Err("A player is already at this location");
+        }
+        if self.is_blocked(position) { return Err("That cell is blocked"); }
+        let Some(player) = self.current_player else {
+            return Err("Cannot move the player. It is not their turn.");
+        };
+        match self.variant {
+            Variant::Ultimate => self.check_ultimate(position)?,
+            Variant::Gravity => self.check_gravity(position)?,
+            Variant::Morris { .. } => self.check_morris(mv, player)?,
+            Variant::Notakto => self.check_notakto(position)?,
+            _ => {}
+        }
+        if let Move::Slide { from, .. } = mv {
+            self.board[from] = None;
+            self.hash ^= zobrist::cell_key(from, player);
+        }
+        // the symbol placed is the mover's own unless they chose one, or there's only one symbol
+        let symbol = match mv {
+            Move::Mark { symbol, .. } => symbol,
+            _ if self.variant == Variant::Notakto => notakto::MARK,
+            _ => player,
+        };
+        self.board[position] = Some(symbol);
+        self.hash ^= zobrist::cell_key(position, symbol);
+        self.moves.push((player, index));
+        // only lines through the new mark can have been completed by it
+        let completed = match self.variant {
+            Variant::Ultimate => self.completes_line(position) && self.meta_winner() == Some(player),
+            Variant::Notakto => self.notakto_moves().is_empty(),
+            _ => self.completes_line(position),
+        };
+        // in elimination games a line knocks its player out, and the game goes on without them
+        let eliminated = completed && self.variant == Variant::Elimination;
+        if eliminated { self.eliminated.push(player); }
+        self.set_current_player(Some(self.next_player(player)));
+        self.history.push(self.hash);
+        if let Some(survivor) = self.survivor() {
+            self.outcome = Some(Outcome::Win(survivor));
+        } else if completed && !eliminated {
+            self.outcome = Some(Outcome::Win(self.line_winner(player)));
+        } else if self.variant == Variant::OrderChaos && self.legal_moves().is_empty() {
+            self.outcome = Some(Outcome::Win(order_chaos::CHAOS));
+        } else if self.legal_moves().is_empty() || self.variant.can_repeat() && self.repetitions() >= REPETITIONS {
+            self.outcome = Some(Outcome::Draw);
+        }
+        if self.is_over() { self.set_current_player(None); }
+        Ok(())
+    }
+    /// Takes back the last move, returning who made it and its encoded move. A forfeit is taken back along with it.
+    pub fn undo(&mut self) -> Option<(Player, usize)> {
+        if self.variant == Variant::Quantum { return self.undo_by_replay(); }
+        let (player, index) = self.moves.pop()?;
+        let (from, to) = match self.decode(index)? {
+            Move::Place(position) | Move::Mark { position, .. } => (None, position),
+            Move::Slide { from, to } => (Some(from), to),
+            Move::Spooky { .. } | Move::Collapse(_) => return None,
+        };
+        let symbol = self.board[to]?;
+        self.board[to] = None;
+        self.hash ^= zobrist::cell_key(to, symbol);
+        if let Some(from) = from {
+            self.board[from] = Some(player);
+            self.hash ^= zobrist::cell_key(from, player);
+        }
+        self.history.pop();
+        self.eliminated.retain(|eliminated| *eliminated != player);
+        self.outcome = None;
+        self.set_current_player(Some(player));
+        Some((player, index))
+    }
+    /// Takes back the last move by playing every move before it again, for collapses that can't be undone directly.
+    fn undo_by_replay(&mut self) -> Option<(Player, usize)> {
+        let (&last, earlier) = self.moves.split_last()?;
+        let mut replay = Game::with_players(self.variant, self.width, self.height, self.win_length, self.players, self.moves[0].0).ok()?;
+        for (_, index) in earlier {
+            replay.play(*index).ok()?;
+        }
+        *self = replay;
+        Some(last)
+    }
+    /// How many times the current position has come up, counting this time.
+    pub fn repetitions(&self) -> usize {
+        self.history.iter().filter(|hash| **hash == self.hash).count()
+    }
+    /// Changes the player to move, keeping the hash in step.
+    fn set_current_player(&mut self, player: Option<Player>) {
+        self.hash ^= zobrist::turn_key(self.current_player) ^ zobrist::turn_key(player);
+        self.current_player = player;
+    }
+    pub fn legal_moves(&self) -> Vec<usize> {
+        if self.is_over() { return Vec::new(); }
+        match self.variant {
+            Variant::Ultimate => return self.ultimate_moves(),
+            Variant::Gravity => return self.gravity_moves(),
+            Variant::Morris { .. } => return self.morris_moves(),
+            Variant::Notakto => return self.notakto_moves(),
+            Variant::Quantum => return self.quantum_moves(),
+            _ => {}
+        }
+        let empty = (0..self.board.len()).filter(|position| self.board[*position].is_none() && !self.is_blocked(*position));
+        if self.variant.chooses_symbols() {
+            return empty.flat_map(|position| [Player::X, Player::O].map(|symbol| self.encode(Move::Mark { position, symbol }))).collect();
+        }
+        empty.collect()
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Player::X)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,47 @@
+//! Three men's morris: each player places as many pieces as it takes to win, and once they're all on
+//! the board a turn moves one of them to an empty cell instead. A position seen three times is drawn,
+//! and so is one where the player to move can't move anything.
+use crate::{Game, Move, Player, Variant};
+
+impl Game {
+    /// How many pieces each player gets.
+    pub fn pieces(&self) -> usize {
+        self.win_length
+    }
+    /// Whether `player` has placed all their pieces, so their turns move one instead.
+    pub fn is_moving(&self, player: Player) -> bool {
+        self.board.iter().filter(|cell| **cell == Some(player)).count() >= self.pieces()
+    }
+    /// Whether a piece on `from` may move to `to`, ignoring what's there. Adjacent includes diagonals.
+    pub fn can_reach(&self, from: usize, to: usize) -> bool {
+        match self.variant {
+            Variant::Morris { adjacent: false } => true,
+            Variant::Morris { adjacent: true } => {
+                let (from_row, from_column) = (from / self.width, from % self.width);
+                let (to_row, to_column) = (to / self.width, to % self.width);
+                from_row.abs_diff(to_row) <= 1 && from_column.abs_diff(to_column) <= 1
+            },
+            _ => false,
+        }
+    }
+    pub(crate) fn check_morris(&self, mv: Move, player: Player) -> Result<(), &'static str> {
+        match mv {
+            Move::Place(_) if self.is_moving(player) => Err("Every piece is placed, move one of them instead"),
+            Move::Slide { .. } if !self.is_moving(player) => Err("Pieces can only move once they're all placed"),
+            Move::Slide { from, .. } if self.board[from] != Some(player) => Err("Players can only move their own pieces"),
+            Move::Slide { from, to } if !self.can_reach(from, to) => Err("Pieces can only move to a neighbouring cell"),
+            _ => Ok(()),
+        }
+    }
+    pub(crate) fn morris_moves(&self) -> Vec<usize> {
+        let Some(player) = self.current_player else { return Vec::new(); };
+        let empty: Vec<usize> = (0..self.board.len()).filter(|position| self.board[*position].is_none()).collect();
+        if !self.is_moving(player) { return empty; }
+        (0..self.board.len())
+            .filter(|from| self.board[*from] == Some(player))
+            .flat_map(|from| empty.iter()
+                .filter(move |to| self.can_reach(from, **to))
+                .map(move |to| self.encode(Move::Slide { from, to: *to })))
+            .collect()
+    }
+}
@@ -0,0 +1,36 @@
+//! Notakto: both players place X on one or more 3x3 boards. A board is dead once it has three in a row
+//! and can't be played on, and whoever kills the last live board loses.
+//!
+//! The boards sit side by side in a single 3-row board, so board `b` covers columns `3b` to `3b + 2`.
+use crate::{Game, Player, Variant};
+
+/// The only symbol anyone places.
+pub const MARK: Player = Player::X;
+
+impl Game {
+    /// A game of Notakto on `boards` boards.
+    pub fn notakto(boards: usize, starting_player: Player) -> Result<Self, &'static str> {
+        Self::with_variant(Variant::Notakto, boards * 3, 3, 3, starting_player)
+    }
+    /// How many 3x3 boards the game is played on.
+    pub fn boards(&self) -> usize {
+        self.width / 3
+    }
+    /// The board position of `cell` within board `board`, both numbered row by row.
+    pub fn board_position(&self, board: usize, cell: usize) -> usize {
+        cell / 3 * self.width + board * 3 + cell % 3
+    }
+    /// Whether `board` has three in a row, so nobody can play on it any more.
+    pub fn is_dead(&self, board: usize) -> bool {
+        (0..9).any(|cell| self.completes_line(self.board_position(board, cell)))
+    }
+    pub(crate) fn check_notakto(&self, position: usize) -> Result<(), &'static str> {
+        if self.is_dead(position % self.width / 3) { return Err("That board is dead"); }
+        Ok(())
+    }
+    pub(crate) fn notakto_moves(&self) -> Vec<usize> {
+        (0..self.board.len())
+            .filter(|position| self.board[*position].is_none() && !self.is_dead(position % self.width / 3))
+            .collect()
+    }
+}
@@ -0,0 +1,16 @@
+//! Order and Chaos: both players place either symbol on a 6x6 board. Order wins as soon as five of
+//! one symbol line up, whoever placed them, and Chaos wins if the board fills up without that.
+use crate::{Game, Player, Variant};
+
+pub const SIZE: usize = 6;
+pub const LINE: usize = 5;
+/// Order plays as X and Chaos as O, so the usual Player works for roles.
+pub const ORDER: Player = Player::X;
+pub const CHAOS: Player = Player::O;
+
+impl Game {
+    /// A game of Order and Chaos, with Order moving first as usual.
+    pub fn order_chaos() -> Self {
+        Self::with_variant(Variant::OrderChaos, SIZE, SIZE, LINE, ORDER).unwrap()
+    }
+}
@@ -0,0 +1,148 @@
+//! Quantum tic-tac-toe on a 3x3 board. A move puts a spooky mark in two cells at once, entangling them.
+//! When spooky marks close a cycle of entanglements, the other player picks which of its two cells the
+//! newest mark collapses into, and every mark entangled with it collapses along with it into a classical
+//! mark. Once a single cell is left it takes an ordinary mark.
+//!
+//! A collapse can complete lines for both players. The line finished earliest, going by the move number
+//! of the latest mark in it, wins.
+use serde::Serialize;
+use crate::{ultimate::LINES, zobrist, Game, Move, Outcome, Player};
+
+/// A mark in two cells at once, `turn` being the move that placed it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Spooky {
+    pub player: Player,
+    pub turn: usize,
+    pub cells: [usize; 2],
+}
+
+/// Everything about a quantum game the board itself can't hold.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Quantum {
+    pub spooky: Vec<Spooky>,        // marks that haven't collapsed yet
+    pub turns: Vec<Option<usize>>,  // the move each classical mark was placed by, indexed by cell
+    pub cycle: Option<usize>,       // the spooky mark that closed a cycle, waiting to be collapsed
+}
+
+impl Quantum {
+    pub fn new(cells: usize) -> Self {
+        Quantum { spooky: Vec::new(), turns: vec![None; cells], cycle: None }
+    }
+}
+
+impl Game {
+    /// Cells without a classical mark, where spooky marks can still go.
+    fn open_cells(&self) -> Vec<usize> {
+        (0..self.board.len()).filter(|cell| self.board[*cell].is_none()).collect()
+    }
+    /// Whether spooky marks already connect `first` and `second`, so a mark between them closes a cycle.
+    fn entangled(&self, first: usize, second: usize) -> bool {
+        let Some(quantum) = &self.quantum else { return false; };
+        let (mut seen, mut queue) = (vec![first], vec![first]);
+        while let Some(cell) = queue.pop() {
+            if cell == second { return true; }
+            for mark in quantum.spooky.iter().filter(|mark| mark.cells.contains(&cell)) {
+                let other = mark.cells[0] + mark.cells[1] - cell;
+                if !seen.contains(&other) {
+                    seen.push(other);
+                    queue.push(other);
+                }
+            }
+        }
+        false
+    }
+    /// Puts spooky mark `mark` in `cell`, forcing every other mark in that cell into its other one and so on.
+    fn collapse(&mut self, mark: usize, cell: usize) {
+        let Some(quantum) = &mut self.quantum else { return; };
+        let mut pending = vec![(quantum.spooky.remove(mark), cell)];
+        while let Some((mark, cell)) = pending.pop() {
+            self.board[cell] = Some(mark.player);
+            self.hash ^= zobrist::cell_key(cell, mark.player);
+            quantum.turns[cell] = Some(mark.turn);
+            let (forced, rest): (Vec<Spooky>, Vec<Spooky>) = quantum.spooky.iter().partition(|other| other.cells.contains(&cell));
+            quantum.spooky = rest;
+            pending.extend(forced.into_iter().map(|other| (other, other.cells[0] + other.cells[1] - cell)));
+        }
+        quantum.cycle = None;
+    }
+    /// Points for X and for O, None while nobody has a line. The player whose line was finished earliest
+    /// scores 1, or 2 if the same move finished two of their lines, and the other player scores a half
+    /// for any line of their own.
+    pub fn quantum_scores(&self) -> Option<[f32; 2]> {
+        let turns = &self.quantum.as_ref()?.turns;
+        let lines: Vec<(Player, usize)> = LINES.iter()
+            .filter_map(|line| {
+                let player = self.board[line[0]]?;
+                if line.iter().any(|cell| self.board[*cell] != Some(player)) { return None; }
+                line.iter().map(|cell| turns[*cell]).max().flatten().map(|turn| (player, turn))
+            })
+            .collect();
+        let (winner, first) = lines.iter().copied().min_by_key(|(_, turn)| *turn)?;
+        let doubled = lines.iter().filter(|line| **line == (winner, first)).count() > 1;
+        let loser = lines.iter().any(|(player, _)| *player != winner);
+        let (winning, losing) = (if doubled { 2.0 } else { 1.0 }, if loser { 0.5 } else { 0.0 });
+        Some(if winner == Player::X { [winning, losing] } else { [losing, winning] })
+    }
+    pub(crate) fn quantum_winner(&self) -> Option<Player> {
+        let [x, o] = self.quantum_scores()?;
+        Some(if x > o { Player::X } else { Player::O })
+    }
+    pub(crate) fn play_quantum(&mut self, index: usize, mv: Move) -> Result<(), &'static str> {
+        let Some(player) = self.current_player else {
+            return Err("Cannot move the player. It is not their turn.");
+        };
+        let Some(quantum) = &self.quantum else { return Err("This game isn't quantum"); };
+        let (cycle, turn, open) = (quantum.cycle, self.moves.len(), self.open_cells());
+        match mv {
+            Move::Collapse(cell) => {
+                let Some(mark) = cycle else { return Err("There's no cycle to collapse"); };
+                if !quantum.spooky[mark].cells.contains(&cell) {
+                    return Err("The mark can only collapse into one of its two cells");
+                }
+                self.collapse(mark, cell);
+            },
+            _ if cycle.is_some() => return Err("The cycle has to be collapsed first"),
+            Move::Spooky { first, second } => {
+                if first == second { return Err("A spooky mark goes in two different cells"); }
+                if self.board[first].is_some() || self.board[second].is_some() {
+                    return Err("A player is already at this location");
+                }
+                let closes = self.entangled(first, second);
+                let quantum = self.quantum.as_mut().unwrap();
+                quantum.spooky.push(Spooky { player, turn, cells: [first, second] });
+                if closes { quantum.cycle = Some(quantum.spooky.len() - 1); }
+            },
+            Move::Place(position) => {
+                if open != [position] { return Err("Marks are spooky until only one cell is left"); }
+                self.board[position] = Some(player);
+                self.hash ^= zobrist::cell_key(position, player);
+                self.quantum.as_mut().unwrap().turns[position] = Some(turn);
+            },
+            Move::Slide { .. } | Move::Mark { .. } => return Err("That move isn't part of quantum tic-tac-toe"),
+        }
+        self.moves.push((player, index));
+        // whoever collapses a cycle goes on to make their own move
+        if !matches!(mv, Move::Collapse(_)) { self.set_current_player(Some(player.opponent())); }
+        self.history.push(self.hash);
+        if let Some(winner) = self.quantum_winner() {
+            self.outcome = Some(Outcome::Win(winner));
+        } else if self.legal_moves().is_empty() {
+            self.outcome = Some(Outcome::Draw);
+        }
+        if self.is_over() { self.set_current_player(None); }
+        Ok(())
+    }
+    pub(crate) fn quantum_moves(&self) -> Vec<usize> {
+        let Some(quantum) = &self.quantum else { return Vec::new(); };
+        if let Some(mark) = quantum.cycle {
+            return quantum.spooky[mark].cells.map(|cell| self.encode(Move::Collapse(cell))).to_vec();
+        }
+        let open = self.open_cells();
+        if open.len() == 1 { return open; }
+        open.iter()
+            .flat_map(|first| open.iter()
+                .filter(move |second| first < second)
+                .map(move |second| self.encode(Move::Spooky { first: *first, second: *second })))
+            .collect()
+    }
+}
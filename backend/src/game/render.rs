@@ -0,0 +1,67 @@
+use std::fmt;
+use crate::{Game, Player, SYMBOLS};
+
+/// How blocked cells are drawn.
+const BLOCKED: &str = "#";
+
+/// How a board is drawn.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RenderStyle {
+    #[default]
+    Ascii,      // pipes and dashes between the cells
+    Unicode,    // a box-drawing grid with a frame around it
+    Compact,    // one line, rows separated by slashes and empty cells as dots
+}
+
+/// Draws boards as text in a chosen style. `Display` for `Game` uses the default, ASCII.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Renderer {
+    pub style: RenderStyle,
+}
+
+impl Renderer {
+    pub fn new(style: RenderStyle) -> Self {
+        Renderer { style }
+    }
+    /// Draws the board, blocked cells as `#`.
+    pub fn render(&self, game: &Game) -> String {
+        let positions: Vec<usize> = (0..game.board.len()).collect();
+        let rows: Vec<&[usize]> = positions.chunks(game.width).collect();
+        let label = |position: &usize, empty: &str| match game.board[*position] {
+            Some(player) => player.to_string(),
+            None if game.is_blocked(*position) => BLOCKED.to_owned(),
+            None => empty.to_owned(),
+        };
+        let cells = |row: &[usize], divider: &str| row.iter()
+            .map(|position| format!(" {} ", label(position, " ")))
+            .collect::<Vec<_>>()
+            .join(divider);
+        match self.style {
+            RenderStyle::Ascii => rows.iter()
+                .map(|row| cells(row, "|"))
+                .collect::<Vec<_>>()
+                .join(&format!("\n{}\n", "-".repeat(game.width * 4 - 1))),
+            RenderStyle::Unicode => {
+                let border = |left: &str, middle: &str, right: &str| format!("{}{}{}", left, vec!["───"; game.width].join(middle), right);
+                let lines: Vec<String> = rows.iter().map(|row| format!("│{}│", cells(row, "│"))).collect();
+                [border("┌", "┬", "┐"), lines.join(&format!("\n{}\n", border("├", "┼", "┤"))), border("└", "┴", "┘")].join("\n")
+            }
+            RenderStyle::Compact => rows.iter()
+                .map(|row| row.iter().map(|position| label(position, ".")).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("/"),
+        }
+    }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(SYMBOLS.get(self.index()).unwrap_or(&"?"))
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Renderer::default().render(self))
+    }
+}
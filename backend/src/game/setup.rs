@@ -0,0 +1,79 @@
+//! Games that start from a prepared position instead of an empty board: cells blocked off so nobody can
+//! play in them and marks already in place, for puzzles or to shake up the opening.
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::{zobrist, Game, Player, Variant};
+
+/// A starting position, with cells numbered the same way as moves.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Setup {
+    pub blocked: Vec<usize>,            // cells nobody can play in
+    pub marks: Vec<(usize, Player)>,    // marks on the board before the first move
+}
+
+impl Setup {
+    /// Up to `obstacles` blocked cells picked at random from `seed`, so the same seed always gives the same
+    /// layout. Every blocked cell has its mirror image through the centre blocked too, so neither side is favoured.
+    pub fn random(width: usize, height: usize, obstacles: usize, seed: u64) -> Self {
+        let cells = width * height;
+        let mut order: Vec<usize> = (0..cells).collect();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut blocked: Vec<usize> = Vec::new();
+        for cell in order {
+            let mirror = cells - 1 - cell;
+            let pair = if mirror == cell { vec![cell] } else { vec![cell, mirror] };
+            if blocked.contains(&cell) || blocked.len() + pair.len() > obstacles { continue; }
+            blocked.extend(pair);
+        }
+        blocked.sort_unstable();
+        Setup { blocked, marks: Vec::new() }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty() && self.marks.is_empty()
+    }
+}
+
+impl Game {
+    /// A game that starts from `setup`. The position has to make sense: every cell on the board and used
+    /// once, marks only for players in the game and shared out as evenly as taking turns would, nobody
+    /// with a line already and a move left to make.
+    pub fn from_setup(
+        variant: Variant, width: usize, height: usize, win_length: usize, players: usize,
+        starting_player: Player, setup: Setup
+    ) -> Result<Self, &'static str> {
+        let mut game = Self::with_players(variant, width, height, win_length, players, starting_player)?;
+        if setup.is_empty() { return Ok(game); }
+        if !variant.allows_setup() { return Err("This variant can only start from an empty board"); }
+        let cells: Vec<usize> = setup.blocked.iter().copied().chain(setup.marks.iter().map(|(cell, _)| *cell)).collect();
+        if cells.iter().any(|cell| *cell >= game.board.len()) { return Err("The starting position has a cell off the board"); }
+        if cells.iter().enumerate().any(|(index, cell)| cells[..index].contains(cell)) {
+            return Err("The starting position uses a cell twice");
+        }
+        for (cell, player) in &setup.marks {
+            if player.index() >= players { return Err("The starting position has a mark for a player who isn't in the game"); }
+            game.board[*cell] = Some(*player);
+        }
+        // players who choose their symbols can have placed any mix of them
+        if !variant.chooses_symbols() {
+            let counts: Vec<usize> = Player::all(players)
+                .map(|player| setup.marks.iter().filter(|(_, mark)| *mark == player).count())
+                .collect();
+            if counts.iter().max().unwrap() - counts.iter().min().unwrap() > 1 {
+                return Err("The starting position gives a player more marks than taking turns would");
+            }
+        }
+        game.hash = zobrist::hash(&game.board, game.current_player);
+        game.history = vec![game.hash];
+        game.setup = setup;
+        if (0..game.board.len()).any(|cell| game.completes_line(cell)) {
+            return Err("A line is already complete in the starting position");
+        }
+        if game.legal_moves().is_empty() { return Err("There's no move left in the starting position"); }
+        Ok(game)
+    }
+    /// Whether `cell` was blocked off before the game began.
+    pub fn is_blocked(&self, cell: usize) -> bool {
+        self.setup.blocked.contains(&cell)
+    }
+}
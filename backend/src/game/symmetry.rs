@@ -0,0 +1,83 @@
+use crate::{zobrist, Game, Player};
+
+/// The rotations and reflections of a board. Rotating clockwise, flips mirror across the middle row or column.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal, // left and right swap
+    FlipVertical,   // top and bottom swap
+    Transpose,      // reflect across the main diagonal
+    AntiTranspose,  // reflect across the other diagonal
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity, Symmetry::Rotate90, Symmetry::Rotate180, Symmetry::Rotate270,
+        Symmetry::FlipHorizontal, Symmetry::FlipVertical, Symmetry::Transpose, Symmetry::AntiTranspose,
+    ];
+    /// The symmetries that keep a board's shape. Rectangular boards can't be rotated a quarter turn.
+    pub fn of(width: usize, height: usize) -> Vec<Symmetry> {
+        if width == height { return Symmetry::ALL.to_vec(); }
+        vec![Symmetry::Identity, Symmetry::Rotate180, Symmetry::FlipHorizontal, Symmetry::FlipVertical]
+    }
+    /// The transformation that undoes this one.
+    pub fn inverse(&self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => *other,
+        }
+    }
+    /// Where `position` ends up once the board is transformed.
+    pub fn apply(&self, position: usize, width: usize, height: usize) -> usize {
+        let (row, column) = (position / width, position % width);
+        let (row, column) = match self {
+            Symmetry::Identity => (row, column),
+            Symmetry::Rotate90 => (column, height - 1 - row),
+            Symmetry::Rotate180 => (height - 1 - row, width - 1 - column),
+            Symmetry::Rotate270 => (width - 1 - column, row),
+            Symmetry::FlipHorizontal => (row, width - 1 - column),
+            Symmetry::FlipVertical => (height - 1 - row, column),
+            Symmetry::Transpose => (column, row),
+            Symmetry::AntiTranspose => (width - 1 - column, height - 1 - row),
+        };
+        // quarter turns and diagonal reflections swap the board's width and height
+        let width = match self {
+            Symmetry::Rotate90 | Symmetry::Rotate270 | Symmetry::Transpose | Symmetry::AntiTranspose => height,
+            _ => width,
+        };
+        row * width + column
+    }
+    pub fn transform(&self, board: &[Option<Player>], width: usize, height: usize) -> Vec<Option<Player>> {
+        let mut transformed = vec![None; board.len()];
+        for (position, cell) in board.iter().enumerate() {
+            transformed[self.apply(position, width, height)] = *cell;
+        }
+        transformed
+    }
+}
+
+/// A position's representative among all its symmetric copies.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canonical {
+    pub board: Vec<Option<Player>>,
+    pub hash: u64,
+    pub symmetry: Symmetry,     // takes the game's board to `board`, its inverse maps moves back
+}
+
+impl Game {
+    /// The canonical form of the position: the smallest of its symmetric boards, so equivalent positions share it.
+    /// Only symmetries that keep the blocked cells blocked count.
+    pub fn canonical(&self) -> Canonical {
+        let order = |cell: &Option<Player>| cell.map_or(0, |player| player.index() + 1);
+        let (board, symmetry) = Symmetry::of(self.width, self.height).into_iter()
+            .filter(|symmetry| self.setup.blocked.iter().all(|cell| self.is_blocked(symmetry.apply(*cell, self.width, self.height))))
+            .map(|symmetry| (symmetry.transform(&self.board, self.width, self.height), symmetry))
+            .min_by(|(a, _), (b, _)| a.iter().map(order).cmp(b.iter().map(order)))
+            .unwrap();
+        Canonical { hash: zobrist::hash(&board, self.current_player), board, symmetry }
+    }
+}
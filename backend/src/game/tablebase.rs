@@ -0,0 +1,138 @@
+use std::{fs, io, path::Path};
+use crate::{analysis::{Analysis, Evaluation, MoveAnalysis}, Game, Outcome, Player, Variant};
+
+const MAGIC: &[u8; 4] = b"TTTB";
+const VERSION: u8 = 1;
+/// Every way of filling nine cells with nothing, the starting player's mark or the other mark.
+const INDICES: usize = 3usize.pow(9);
+const NO_MOVE: u8 = u8::MAX;
+
+/// A tablebase entry: the value of a position for the player to move and the move that achieves it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Entry {
+    pub value: Evaluation,
+    pub best: Option<usize>,
+}
+
+/// The solved value of every reachable 3x3 position, looked up by indexing the board.
+/// Positions are stored relative to the starting player, so one table serves games started by either.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tablebase {
+    entries: Vec<Option<Entry>>,    // indexed by the board read as a base 3 number
+}
+
+/// The table index of a 3x3 board, or None for other sizes and games that can't be looked up.
+fn index(game: &Game) -> Option<usize> {
+    if game.variant != Variant::Standard || game.players != 2 || game.width != 3 || game.height != 3 || game.win_length != 3 { return None; }
+    if matches!(game.outcome, Some(Outcome::Forfeit(_))) || !game.setup.is_empty() { return None; }
+    let starter = game.moves.first().map(|(player, _)| *player).or(game.current_player)?;
+    Some(game.board.iter().rev().fold(0, |index, cell| index * 3 + match cell {
+        None => 0,
+        Some(player) if *player == starter => 1,
+        Some(_) => 2,
+    }))
+}
+
+fn encode(entry: Option<Entry>) -> [u8; 2] {
+    let Some(entry) = entry else { return [0, NO_MOVE]; };
+    let value = match entry.value {
+        Evaluation::Draw => 1,
+        Evaluation::Win(distance) => 0x10 | distance,
+        Evaluation::Loss(distance) => 0x20 | distance,
+    };
+    [value, entry.best.map_or(NO_MOVE, |best| best as u8)]
+}
+
+fn decode(bytes: &[u8]) -> Result<Option<Entry>, &'static str> {
+    let value = match bytes[0] {
+        0 => return Ok(None),
+        1 => Evaluation::Draw,
+        byte if byte & 0xf0 == 0x10 => Evaluation::Win(byte & 0x0f),
+        byte if byte & 0xf0 == 0x20 => Evaluation::Loss(byte & 0x0f),
+        _ => return Err("The tablebase has an invalid value"),
+    };
+    let best = match bytes[1] {
+        NO_MOVE => None,
+        best if best < 9 => Some(best as usize),
+        _ => return Err("The tablebase has an invalid move"),
+    };
+    Ok(Some(Entry { value, best }))
+}
+
+impl Tablebase {
+    /// Solves every position reachable from the empty board.
+    pub fn generate() -> Self {
+        let mut tablebase = Tablebase { entries: vec![None; INDICES] };
+        tablebase.solve(&Game::new(Player::X));
+        tablebase
+    }
+    fn solve(&mut self, game: &Game) -> Evaluation {
+        let index = index(game).unwrap();
+        if let Some(entry) = self.entries[index] { return entry.value; }
+        let entry = match game.outcome {
+            Some(Outcome::Draw) => Entry { value: Evaluation::Draw, best: None },
+            // the game ended on the previous move, so the player to move has lost it
+            Some(_) => Entry { value: Evaluation::Loss(0), best: None },
+            None => game.legal_moves().into_iter()
+                .map(|position| {
+                    let mut next = game.clone();
+                    next.play(position).unwrap();
+                    Entry { value: self.solve(&next).flip(), best: Some(position) }
+                })
+                .max_by_key(|entry| entry.value.score())
+                .unwrap(),
+        };
+        self.entries[index] = Some(entry);
+        entry.value
+    }
+    /// How many positions the table holds, finished ones included.
+    pub fn positions(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.is_some()).count()
+    }
+    pub fn get(&self, game: &Game) -> Option<Entry> {
+        self.entries[index(game)?]
+    }
+    pub fn best_move(&self, game: &Game) -> Option<usize> {
+        self.get(game)?.best
+    }
+    /// The same analysis as `analysis::analyze`, read from the table instead of searched.
+    pub fn analyze(&self, game: &Game) -> Option<Analysis> {
+        let entry = self.get(game)?;
+        let moves = game.legal_moves().into_iter()
+            .map(|position| {
+                let mut next = game.clone();
+                next.play(position).ok()?;
+                Some(MoveAnalysis { position, value: self.get(&next)?.value.flip() })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Analysis {
+            player: game.current_player,
+            value: game.current_player.map(|_| entry.value),
+            moves,
+            best: entry.best,
+        })
+    }
+    /// The table as a file: a header followed by two bytes, value and best move, for every index.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(MAGIC.len() + 1 + INDICES * 2);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend(self.entries.iter().flat_map(|entry| encode(*entry)));
+        bytes
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, &'static str> {
+        let Some(body) = bytes.strip_prefix(MAGIC.as_slice()) else { return Err("The file isn't a tablebase"); };
+        let Some((version, body)) = body.split_first() else { return Err("The tablebase is truncated"); };
+        if *version != VERSION { return Err("The tablebase version isn't supported"); }
+        if body.len() != INDICES * 2 { return Err("The tablebase is truncated"); }
+        let entries = body.chunks(2).map(decode).collect::<Result<_, _>>()?;
+        Ok(Tablebase { entries })
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Tablebase::from_bytes(&bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
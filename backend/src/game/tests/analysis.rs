@@ -0,0 +1,44 @@
+use crate::{analysis::{analyze, best_move, legal_moves, Evaluation}, Game, Player};
+use super::play;
+
+#[test]
+fn test_empty_board_is_a_draw() {
+    let analysis = analyze(&Game::new(Player::X));
+    assert_eq!(analysis.player, Some(Player::X));
+    assert_eq!(analysis.value, Some(Evaluation::Draw));
+    assert_eq!(analysis.moves.len(), 9);
+    assert!(analysis.moves.iter().all(|analysis| analysis.value == Evaluation::Draw));
+}
+
+#[test]
+fn test_finds_winning_move() {
+    // X: 0, 1 / O: 3, 4 -> X wins immediately at 2
+    let game = play(&[0, 3, 1, 4]);
+    let analysis = analyze(&game);
+    assert_eq!(analysis.best, Some(2));
+    assert_eq!(analysis.value, Some(Evaluation::Win(1)));
+    // letting O complete their row loses on the next move
+    let blunder = analysis.moves.iter().find(|analysis| analysis.position == 8).unwrap();
+    assert_eq!(blunder.value, Evaluation::Loss(2));
+}
+
+#[test]
+fn test_finds_forced_win_and_block() {
+    // X takes a corner, O answers on an edge, which loses to best play
+    let game = play(&[0, 1]);
+    let analysis = analyze(&game);
+    assert!(matches!(analysis.value, Some(Evaluation::Win(_))));
+    // O must block X's threat on the diagonal
+    let game = play(&[0, 3, 4]);
+    assert_eq!(best_move(&game), Some(8));
+}
+
+#[test]
+fn test_finished_game() {
+    let game = play(&[0, 3, 1, 4, 2]);
+    let analysis = analyze(&game);
+    assert_eq!(analysis.player, None);
+    assert!(analysis.moves.is_empty());
+    assert_eq!(analysis.best, None);
+    assert!(legal_moves(&game).is_empty());
+}
@@ -0,0 +1,65 @@
+use crate::{bitboard::{Bitboard, WinMasks}, Game, Player};
+use super::play_on;
+
+#[test]
+fn test_win_masks() {
+    assert_eq!(WinMasks::new(3, 3, 3).lines.len(), 8);
+    assert_eq!(WinMasks::new(4, 4, 3).lines.len(), 24);
+    // the centre of a 3x3 board is on both diagonals, its row and its column
+    assert_eq!(WinMasks::new(3, 3, 3).through[4].len(), 4);
+}
+
+#[test]
+fn test_board_round_trip() {
+    let game = play_on(Game::with_size(5, 4, 4, Player::O).unwrap(), &[0, 7, 19, 12]);
+    let bitboard = Bitboard::from_game(&game).unwrap();
+    assert_eq!(bitboard.to_board(), game.board);
+    assert_eq!(bitboard.to_move, Player::O);
+    assert_eq!(bitboard.moves().count(), game.legal_moves().len());
+    assert!(Bitboard::from_game(&Game::with_size(9, 9, 5, Player::X).unwrap()).is_err());
+}
+
+#[test]
+fn test_make_unmake() {
+    let mut bitboard = Bitboard::new(3, 3, 3, Player::X).unwrap();
+    let empty = bitboard.clone();
+    for cell in [0, 3, 1, 4] {
+        bitboard.make_move(cell);
+        assert!(!bitboard.wins_at(cell));
+    }
+    bitboard.make_move(2);
+    assert!(bitboard.wins_at(2));
+    assert_eq!(bitboard.winner(), Some(Player::X));
+    assert_eq!(bitboard.legal_moves(), 0);
+    for cell in [2, 4, 1, 3, 0] {
+        bitboard.unmake_move(cell);
+    }
+    assert_eq!(bitboard, empty);
+}
+
+#[test]
+fn test_matches_game() {
+    let mut game = Game::new(Player::X);
+    let mut bitboard = Bitboard::from_game(&game).unwrap();
+    for position in [4, 0, 8, 2, 1, 7, 6, 3, 5] {
+        game.play(position).unwrap();
+        bitboard.make_move(position);
+        assert_eq!(bitboard.winner(), game.winner());
+    }
+    assert!(bitboard.is_full());
+    assert_eq!(bitboard.to_board(), game.board);
+}
+
+#[test]
+fn test_winner_of_loaded_board() {
+    let x = Some(Player::X);
+    let o = Some(Player::O);
+    let board = [x, x, x, o, o, None, None, None, None];
+    let bitboard = Bitboard::from_board(&board, 3, 3, 3, Player::O).unwrap();
+    assert_eq!(bitboard.winner(), Some(Player::X));
+    assert_eq!(bitboard.legal_moves(), 0);
+    // a third player has no mask to go in
+    let board = [x, o, Some(Player(2)), None, None, None, None, None, None];
+    assert!(Bitboard::from_board(&board, 3, 3, 3, Player::X).is_err());
+    assert!(Bitboard::from_board(&[None; 9], 3, 3, 3, Player(2)).is_err());
+}
@@ -0,0 +1,33 @@
+use crate::{engine::{Engine, MinimaxEngine, RandomEngine}, Game, Player};
+use super::play;
+
+#[test]
+fn test_minimax_engine() {
+    let mut engine = MinimaxEngine::new();
+    let mut game = play(&[0, 3, 1, 4]);
+    assert_eq!(engine.choose_move(&game), Some(2));
+    game.play(2).unwrap();
+    assert_eq!(engine.choose_move(&game), None);
+}
+
+#[test]
+fn test_minimax_self_play_draws() {
+    let mut engine = MinimaxEngine::new();
+    let mut game = Game::new(Player::X);
+    while let Some(position) = engine.choose_move(&game) {
+        game.play(position).unwrap();
+    }
+    assert_eq!(game.winner(), None);
+}
+
+#[test]
+fn test_random_engine() {
+    let mut game = Game::new(Player::X);
+    let (mut engine, mut same_seed) = (RandomEngine::new(7), RandomEngine::new(7));
+    while let Some(position) = engine.choose_move(&game) {
+        assert_eq!(same_seed.choose_move(&game), Some(position));
+        assert!(game.board[position].is_none());
+        game.play(position).unwrap();
+    }
+    assert!(game.is_over());
+}
@@ -0,0 +1,93 @@
+use crate::{Game, Outcome, Player};
+use super::play;
+
+#[test]
+fn test_game() {
+    let mut game: Game = Game::new(Player::X);
+    assert_eq!(game.current_player, Some(Player::X));
+    assert!(game.board.iter().all(|x| x.is_none()));
+    let _ = game.move_player(&Player::X, 0);
+    assert_eq!(game.board[0], Some(Player::X));
+    let _ = game.move_player(&Player::O, 4);
+    assert_eq!(game.board[4], Some(Player::O));
+    let _ = game.move_player(&Player::X, 8);
+    assert_eq!(game.board[8], Some(Player::X));
+    let _ = game.move_player(&Player::O, 2);
+    assert_eq!(game.board[2], Some(Player::O));
+    let _ = game.move_player(&Player::X, 6);
+    assert_eq!(game.board[6], Some(Player::X));
+    let _ = game.move_player(&Player::O, 7);
+    assert_eq!(game.board[7], Some(Player::O));
+    let _ = game.move_player(&Player::X, 3);
+    assert_eq!(game.board[3], Some(Player::X));
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.current_player, None);
+    assert!(game.move_player(&Player::O, 5).is_err());
+}
+
+#[test]
+fn test_draw_and_forfeit() {
+    let mut game: Game = Game::new(Player::X);
+    for (player, position) in [(Player::X, 0), (Player::O, 1), (Player::X, 2), (Player::O, 4), (Player::X, 3),
+                               (Player::O, 5), (Player::X, 7), (Player::O, 6), (Player::X, 8)] {
+        game.move_player(&player, position).unwrap();
+    }
+    assert_eq!(game.outcome, Some(Outcome::Draw));
+    game.forfeit(&Player::O); // a finished game can't be forfeited
+    assert_eq!(game.outcome, Some(Outcome::Draw));
+
+    let mut game: Game = Game::new(Player::X);
+    assert!(game.move_player(&Player::X, 9).is_err());
+    game.forfeit(&Player::X);
+    assert_eq!(game.outcome.and_then(|outcome| outcome.winner()), Some(Player::O));
+}
+
+#[test]
+fn test_starting_player() {
+    let mut game: Game = Game::new(Player::O);
+    assert_eq!(game.current_player, Some(Player::O));
+    assert!(game.move_player(&Player::X, 0).is_err());
+    game.move_player(&Player::O, 0).unwrap();
+    assert_eq!(game.board[0], Some(Player::O));
+    assert_eq!(game.current_player, Some(Player::X));
+    game.forfeit(&Player::X);
+    assert_eq!(game.outcome.and_then(|outcome| outcome.winner()), Some(Player::O));
+}
+
+#[test]
+fn test_larger_board() {
+    assert!(Game::with_size(0, 3, 3, Player::X).is_err());
+    assert!(Game::with_size(3, 3, 4, Player::X).is_err());
+    let mut game: Game = Game::with_size(5, 4, 4, Player::X).unwrap();
+    assert_eq!(game.board.len(), 20);
+    assert!(game.move_player(&Player::X, 20).is_err());
+    // X builds the diagonal down from the top right corner, O's three in a row isn't enough
+    for (x, o) in [(4, 0), (8, 1), (12, 2)] {
+        game.move_player(&Player::X, x).unwrap();
+        game.move_player(&Player::O, o).unwrap();
+    }
+    assert_eq!(game.outcome, None);
+    game.move_player(&Player::X, 16).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.winner(), Some(Player::X));
+}
+
+#[test]
+fn test_play() {
+    let mut game: Game = Game::new(Player::O);
+    game.play(4).unwrap();
+    assert_eq!(game.board[4], Some(Player::O));
+    assert!(game.play(4).is_err());
+    assert_eq!(game.legal_moves().len(), 8);
+}
+
+#[test]
+fn test_undo() {
+    let empty = Game::new(Player::X);
+    let mut game = play(&[0, 3, 1, 4, 2]);
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.undo(), Some((Player::X, 2)));
+    assert_eq!((game.outcome, game.current_player, game.board[2]), (None, Some(Player::X), None));
+    while game.undo().is_some() {}
+    assert_eq!((game.board, game.hash, game.current_player), (empty.board, empty.hash, empty.current_player));
+}
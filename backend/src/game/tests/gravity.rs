@@ -0,0 +1,56 @@
+use crate::{analysis::{Evaluation, Solver}, engine::{Engine, MinimaxEngine}, Game, Outcome, Player, Variant};
+
+fn connect_four() -> Game {
+    Game::with_variant(Variant::Gravity, 7, 6, 4, Player::X).unwrap()
+}
+
+#[test]
+fn test_marks_fall() {
+    let mut game = connect_four();
+    assert_eq!(game.landing(3), Some(38));
+    assert_eq!(game.drop(3), Ok(38));
+    assert_eq!(game.drop(3), Ok(31));
+    assert_eq!(game.board[31], Some(Player::O));
+    // a mark can't float above an empty cell
+    assert!(game.play(4).is_err());
+    assert!(game.play(24).is_ok());
+    assert_eq!(game.legal_moves(), vec![35, 36, 37, 17, 39, 40, 41]);
+    assert!(game.drop(7).is_err());
+}
+
+#[test]
+fn test_full_column() {
+    let mut game = Game::with_variant(Variant::Gravity, 2, 2, 2, Player::X).unwrap();
+    game.drop(0).unwrap();
+    game.drop(1).unwrap();
+    assert_eq!(game.landing(0), Some(0));
+    game.drop(0).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.landing(0), None);
+    assert!(game.drop(0).is_err());
+}
+
+#[test]
+fn test_connect_four() {
+    let mut game = connect_four();
+    // X builds a row along the bottom while O stacks on top of it
+    for column in [0, 0, 1, 1, 2, 2] {
+        game.drop(column).unwrap();
+    }
+    assert_eq!(game.outcome, None);
+    game.drop(3).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+}
+
+#[test]
+fn test_engines() {
+    // the engines only ever consider landing cells, and on a 3x3 drop board perfect play draws
+    let mut game = Game::with_variant(Variant::Gravity, 3, 3, 3, Player::X).unwrap();
+    assert_eq!(Solver::default().evaluate(&game), Evaluation::Draw);
+    let mut engine = MinimaxEngine::new();
+    while let Some(position) = engine.choose_move(&game) {
+        assert!(game.legal_moves().contains(&position));
+        game.play(position).unwrap();
+    }
+    assert_eq!(game.outcome, Some(Outcome::Draw));
+}
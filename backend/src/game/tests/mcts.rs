@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+use crate::{engine::Engine, mcts::{MctsConfig, MctsEngine}, Game, Player};
+use super::{play, play_on};
+
+fn engine(iterations: u32, seed: u64) -> MctsEngine {
+    MctsEngine::new(MctsConfig { iterations: Some(iterations), seed, ..Default::default() })
+}
+
+#[test]
+fn test_takes_immediate_win() {
+    let game = play(&[0, 3, 1, 4]);
+    assert_eq!(engine(2_000, 1).choose_move(&game), Some(2));
+}
+
+#[test]
+fn test_blocks_on_large_board() {
+    // O has three in a row on a 7x7 board with four to win, X has already closed one end
+    let game = play_on(Game::with_size(7, 7, 4, Player::X).unwrap(), &[0, 23, 22, 24, 42, 25]);
+    assert_eq!(engine(3_000, 3).choose_move(&game), Some(26));
+}
+
+#[test]
+fn test_seeded_search_is_reproducible() {
+    let game = play_on(Game::with_size(5, 5, 4, Player::X).unwrap(), &[12]);
+    let first = engine(500, 42).search(&game);
+    let second = engine(500, 42).search(&game);
+    assert_eq!(first, second);
+    assert_eq!(first.iter().map(|(_, visits)| visits).sum::<u32>(), 500);
+}
+
+#[test]
+fn test_time_budget() {
+    let game = Game::with_size(15, 15, 5, Player::X).unwrap();
+    let mut engine = MctsEngine::new(MctsConfig {
+        iterations: None,
+        time_limit: Some(Duration::from_millis(100)),
+        ..Default::default()
+    });
+    let started = Instant::now();
+    assert!(engine.choose_move(&game).is_some());
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert!(engine.choose_move(&Game::default()).is_some());
+}
@@ -0,0 +1,36 @@
+use crate::{analysis::{Evaluation, Solver}, engine::{Engine, MinimaxEngine}, mcts::{MctsConfig, MctsEngine}, Game, Outcome, Player, Variant};
+use super::play_on;
+
+fn misere(moves: &[usize]) -> Game {
+    play_on(Game::with_variant(Variant::Misere, 3, 3, 3, Player::X).unwrap(), moves)
+}
+
+#[test]
+fn test_completing_a_line_loses() {
+    let game = misere(&[0, 3, 1, 4, 2]);
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::O)));
+    assert_eq!(game.winner(), Some(Player::O));
+    assert!(game.is_over());
+}
+
+#[test]
+fn test_solver() {
+    let mut solver = Solver::default();
+    // with the centre and mirrored replies the first player never has to complete a line
+    assert_eq!(solver.evaluate(&misere(&[])), Evaluation::Draw);
+    // O can finish its middle row and lose, or play 2 and draw
+    let game = misere(&[0, 3, 1, 4, 6, 8, 7]);
+    assert_eq!(game.legal_moves(), vec![2, 5]);
+    assert_eq!(solver.evaluate(&misere(&[0, 3, 1, 4, 6, 8, 7, 5])), Evaluation::Win(0));
+    assert_eq!(solver.evaluate_move(&game, &Player::O, 5), Evaluation::Loss(1));
+    assert_eq!(solver.evaluate(&game), Evaluation::Draw);
+}
+
+#[test]
+fn test_engines_avoid_lines() {
+    // X holds 0 and 1, so 2 would lose on the spot
+    let game = misere(&[0, 4, 1, 8]);
+    assert_ne!(MinimaxEngine::new().choose_move(&game), Some(2));
+    let mut mcts = MctsEngine::new(MctsConfig { iterations: Some(2_000), ..Default::default() });
+    assert_ne!(mcts.choose_move(&game), Some(2));
+}
@@ -0,0 +1,33 @@
+use crate::{Game, Player};
+
+mod analysis;
+mod bitboard;
+mod engine;
+mod game;
+mod gravity;
+mod mcts;
+mod misere;
+mod morris;
+mod notakto;
+mod order_chaos;
+mod players;
+mod quantum;
+mod render;
+mod setup;
+mod symmetry;
+mod tablebase;
+mod ultimate;
+mod wild;
+
+/// A standard game with X starting, after `moves` have been played in turn.
+fn play(moves: &[usize]) -> Game {
+    play_on(Game::new(Player::X), moves)
+}
+
+/// `game` after `moves` have been played in turn.
+fn play_on(mut game: Game, moves: &[usize]) -> Game {
+    for position in moves {
+        game.play(*position).unwrap();
+    }
+    game
+}
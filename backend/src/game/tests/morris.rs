@@ -0,0 +1,83 @@
+use crate::{mcts::{MctsConfig, MctsEngine}, engine::Engine, zobrist, Game, Move, Outcome, Player, Variant};
+use super::play_on;
+
+/// A 3x3 game with every piece placed and nobody winning yet: X on 0, 2, 7 and O on 1, 3, 4, X to move.
+fn placed(adjacent: bool) -> Game {
+    play_on(Game::with_variant(Variant::Morris { adjacent }, 3, 3, 3, Player::X).unwrap(), &[0, 1, 2, 4, 7, 3])
+}
+
+fn slide(game: &mut Game, from: usize, to: usize) -> Result<(), &'static str> {
+    game.play(game.encode(Move::Slide { from, to }))
+}
+
+#[test]
+fn test_placing_then_moving() {
+    let mut game = placed(true);
+    assert!(game.is_moving(Player::X));
+    assert!(game.play(5).is_err());
+    // O's pieces aren't X's to move, 0 and 8 aren't neighbours and 1 is taken
+    assert!(slide(&mut game, 1, 5).is_err());
+    assert!(slide(&mut game, 0, 8).is_err());
+    assert!(slide(&mut game, 0, 1).is_err());
+    assert_eq!(game.legal_moves().len(), 4);
+    slide(&mut game, 2, 5).unwrap();
+    assert_eq!((game.board[2], game.board[5]), (None, Some(Player::X)));
+    assert_eq!(game.decode(game.moves.last().unwrap().1), Some(Move::Slide { from: 2, to: 5 }));
+    // moving anywhere lets pieces jump across the board
+    let mut game = placed(false);
+    assert!(slide(&mut game, 0, 8).is_ok());
+    let mut game = Game::with_variant(Variant::Morris { adjacent: false }, 3, 3, 3, Player::X).unwrap();
+    game.play(0).unwrap();
+    assert!(slide(&mut game, 0, 1).is_err());
+    assert!(Game::with_variant(Variant::Morris { adjacent: true }, 2, 3, 3, Player::X).is_err());
+}
+
+#[test]
+fn test_moving_wins() {
+    let mut game = placed(true);
+    slide(&mut game, 2, 5).unwrap();
+    slide(&mut game, 1, 2).unwrap();
+    slide(&mut game, 5, 8).unwrap();
+    assert_eq!(game.outcome, None);
+    slide(&mut game, 3, 6).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::O)));
+}
+
+#[test]
+fn test_repetition() {
+    let mut game = placed(true);
+    let start = game.hash;
+    for _ in 0..2 {
+        assert_eq!(game.outcome, None);
+        for (from, to) in [(2, 5), (3, 6), (5, 2), (6, 3)] {
+            slide(&mut game, from, to).unwrap();
+        }
+    }
+    // the third time the position comes up ends the game
+    assert_eq!(game.outcome, Some(Outcome::Draw));
+    assert_eq!(game.history.iter().filter(|hash| **hash == start).count(), 3);
+    game.undo();
+    assert_eq!(game.outcome, None);
+    assert_eq!(game.repetitions(), 2);
+}
+
+#[test]
+fn test_undo_slide() {
+    let mut game = placed(true);
+    let (board, hash) = (game.board.clone(), game.hash);
+    slide(&mut game, 2, 5).unwrap();
+    assert_eq!(game.hash, zobrist::hash(&game.board, game.current_player));
+    assert_eq!(game.undo(), Some((Player::X, game.encode(Move::Slide { from: 2, to: 5 }))));
+    assert_eq!((game.board, game.hash, game.history.len()), (board, hash, 7));
+}
+
+#[test]
+fn test_engine() {
+    let mut game = Game::with_variant(Variant::Morris { adjacent: true }, 3, 3, 3, Player::X).unwrap();
+    let mut engine = MctsEngine::new(MctsConfig { iterations: Some(200), seed: 7, ..Default::default() });
+    while let Some(index) = engine.choose_move(&game) {
+        assert!(game.legal_moves().contains(&index));
+        game.play(index).unwrap();
+    }
+    assert!(game.is_over());
+}
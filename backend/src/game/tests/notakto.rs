@@ -0,0 +1,39 @@
+use crate::{analysis::{Evaluation, Solver}, notakto::MARK, Game, Outcome, Player, Variant};
+
+#[test]
+fn test_dead_boards() {
+    let mut game = Game::notakto(2, Player::X).unwrap();
+    assert_eq!((game.width, game.boards()), (6, 2));
+    assert_eq!(game.board_position(1, 4), 10);
+    // everyone places X, and a line kills its board without ending the game
+    for cell in [0, 1, 2] {
+        game.play(game.board_position(0, cell)).unwrap();
+    }
+    assert!(game.board.iter().flatten().all(|symbol| *symbol == MARK));
+    assert!(game.is_dead(0) && !game.is_dead(1));
+    assert_eq!(game.outcome, None);
+    assert!(game.play(game.board_position(0, 4)).is_err());
+    assert_eq!(game.legal_moves().len(), 9);
+    // lines don't run from one board into the next
+    assert_eq!(game.current_player, Some(Player::O));
+    game.play(game.board_position(1, 0)).unwrap();
+    assert!(!game.is_dead(1));
+    game.play(game.board_position(1, 1)).unwrap();
+    assert_eq!(game.outcome, None);
+    // O kills the last board and loses
+    game.play(game.board_position(1, 2)).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.winner(), Some(Player::X));
+    game.undo();
+    assert_eq!((game.outcome, game.board[5]), (None, None));
+    assert!(Game::with_variant(Variant::Notakto, 4, 3, 3, Player::X).is_err());
+}
+
+#[test]
+fn test_solver() {
+    // on a single board the first player wins by taking the centre
+    let game = Game::notakto(1, Player::X).unwrap();
+    assert!(matches!(Solver::default().evaluate(&game), Evaluation::Win(_)));
+    let mut solver = Solver::default();
+    assert!(matches!(solver.evaluate_move(&game, &Player::X, 4), Evaluation::Win(_)));
+}
@@ -0,0 +1,49 @@
+use crate::{order_chaos::{CHAOS, ORDER}, Game, Outcome, Player, Variant};
+
+#[test]
+fn test_order_wins() {
+    let mut game = Game::order_chaos();
+    assert_eq!(game.legal_moves().len(), 72);
+    // Chaos can be forced into finishing the line, which still counts for Order
+    for position in 0..4 {
+        game.move_symbol(&game.current_player.unwrap(), position, Player::O).unwrap();
+    }
+    assert_eq!(game.current_player, Some(ORDER));
+    game.move_symbol(&ORDER, 10, Player::X).unwrap();
+    game.move_symbol(&CHAOS, 4, Player::O).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(ORDER)));
+    assert_eq!(game.winner(), Some(ORDER));
+}
+
+#[test]
+fn test_overline_does_not_win() {
+    let mut game = Game::order_chaos();
+    for position in [0, 1, 2, 3, 5, 4] {
+        game.move_symbol(&game.current_player.unwrap(), position, Player::X).unwrap();
+    }
+    // six in a row is one too many for Order
+    assert!(!game.completes_line(4));
+    assert_eq!(game.outcome, None);
+}
+
+#[test]
+fn test_chaos_wins() {
+    let mut game = Game::order_chaos();
+    // pairs of symbols shifted every row never line up five
+    let symbol = |position: usize| [Player::X, Player::O][(position % 6 / 2 + position / 6) % 2];
+    for position in 0..35 {
+        game.board[position] = Some(symbol(position));
+    }
+    assert_eq!(game.winner(), None);
+    game.move_symbol(&ORDER, 35, symbol(35)).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(CHAOS)));
+    assert_eq!(game.winner(), Some(CHAOS));
+}
+
+#[test]
+fn test_roles() {
+    assert_eq!(Variant::OrderChaos.role(Player::X), Some("Order"));
+    assert_eq!(Variant::OrderChaos.role(Player::O), Some("Chaos"));
+    assert_eq!(Variant::Standard.role(Player::O), None);
+    assert!(Game::with_variant(Variant::OrderChaos, 3, 3, 3, Player::X).is_err());
+}
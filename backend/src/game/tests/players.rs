@@ -0,0 +1,65 @@
+use crate::{zobrist, Game, Outcome, Player, Variant};
+
+fn three_players(variant: Variant) -> Game {
+    Game::with_players(variant, 4, 4, 3, 3, Player::X).unwrap()
+}
+
+#[test]
+fn test_turn_rotation() {
+    let mut game = three_players(Variant::Standard);
+    for (player, position) in [(Player::X, 0), (Player::O, 4), (Player(2), 8), (Player::X, 1)] {
+        assert_eq!(game.current_player, Some(player));
+        game.move_player(&player, position).unwrap();
+    }
+    assert_eq!(game.current_player, Some(Player::O));
+    assert_eq!(game.hash, zobrist::hash(&game.board, game.current_player));
+    assert_eq!(game.undo(), Some((Player::X, 1)));
+    assert_eq!(game.current_player, Some(Player::X));
+    // the first line still wins outright
+    for (player, position) in [(Player::X, 1), (Player::O, 5), (Player(2), 12), (Player::X, 2)] {
+        game.move_player(&player, position).unwrap();
+    }
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert!(Game::with_players(Variant::Misere, 4, 4, 3, 3, Player::X).is_err());
+    assert!(Game::with_players(Variant::Standard, 4, 4, 3, 5, Player::X).is_err());
+    assert!(Game::with_players(Variant::Standard, 4, 4, 3, 3, Player(3)).is_err());
+}
+
+#[test]
+fn test_elimination() {
+    let mut game = three_players(Variant::Elimination);
+    for (player, position) in [(Player::X, 0), (Player::O, 4), (Player(2), 8), (Player::X, 1), (Player::O, 5), (Player(2), 12)] {
+        game.move_player(&player, position).unwrap();
+    }
+    // X completes a line and is out, so the other two play on
+    game.move_player(&Player::X, 2).unwrap();
+    assert_eq!(game.eliminated, [Player::X]);
+    assert_eq!(game.outcome, None);
+    game.move_player(&Player::O, 9).unwrap();
+    assert_eq!(game.current_player, Some(Player(2)));
+    game.move_player(&Player(2), 13).unwrap();
+    assert_eq!(game.current_player, Some(Player::O));
+    assert_eq!(game.undo(), Some((Player(2), 13)));
+    // O completing a line of their own leaves Y as the last one standing
+    game.move_player(&Player(2), 15).unwrap();
+    game.move_player(&Player::O, 6).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player(2))));
+}
+
+#[test]
+fn test_forfeit_eliminates() {
+    let mut game = three_players(Variant::Standard);
+    game.forfeit(&Player::X);
+    assert_eq!(game.outcome, None);
+    assert_eq!(game.current_player, Some(Player::O));
+    game.forfeit(&Player(2));
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::O)));
+}
+
+#[test]
+fn test_serialize_players() {
+    assert_eq!(serde_json::to_string(&Player(2)).unwrap(), r#""Y""#);
+    assert_eq!(serde_json::from_str::<Player>(r#""Z""#).unwrap(), Player(3));
+    assert!(serde_json::from_str::<Player>(r#""W""#).is_err());
+    assert_eq!(Player(3).to_string(), "Z");
+}
@@ -0,0 +1,90 @@
+use crate::{engine::Engine, mcts::{MctsConfig, MctsEngine}, Game, Move, Outcome, Player, Variant};
+
+fn quantum() -> Game {
+    Game::with_variant(Variant::Quantum, 3, 3, 3, Player::X).unwrap()
+}
+
+fn play_move(game: &mut Game, mv: Move) -> Result<(), &'static str> {
+    game.play(game.encode(mv))
+}
+
+#[test]
+fn test_spooky_marks() {
+    let mut game = quantum();
+    assert_eq!(game.legal_moves().len(), 36);
+    play_move(&mut game, Move::Spooky { first: 0, second: 1 }).unwrap();
+    assert!(game.board.iter().all(Option::is_none));
+    assert_eq!(game.quantum.as_ref().unwrap().spooky.len(), 1);
+    assert_eq!(game.current_player, Some(Player::O));
+    assert!(play_move(&mut game, Move::Spooky { first: 2, second: 2 }).is_err());
+    assert!(play_move(&mut game, Move::Collapse(0)).is_err());
+    assert!(game.play(4).is_err());
+    assert!(!Variant::Quantum.is_searchable());
+}
+
+#[test]
+fn test_collapse() {
+    let mut game = quantum();
+    play_move(&mut game, Move::Spooky { first: 0, second: 1 }).unwrap();
+    play_move(&mut game, Move::Spooky { first: 0, second: 1 }).unwrap();
+    // O closed a cycle, so X chooses how it collapses before moving
+    assert_eq!(game.current_player, Some(Player::X));
+    assert_eq!(game.legal_moves(), vec![game.encode(Move::Collapse(0)), game.encode(Move::Collapse(1))]);
+    assert!(play_move(&mut game, Move::Spooky { first: 2, second: 3 }).is_err());
+    assert!(play_move(&mut game, Move::Collapse(2)).is_err());
+    play_move(&mut game, Move::Collapse(0)).unwrap();
+    assert_eq!((game.board[0], game.board[1]), (Some(Player::O), Some(Player::X)));
+    assert_eq!(game.current_player, Some(Player::X));
+    assert!(game.quantum.as_ref().unwrap().spooky.is_empty());
+    // collapses are taken back by replaying the game
+    assert_eq!(game.undo(), Some((Player::X, game.encode(Move::Collapse(0)))));
+    assert!(game.board.iter().all(Option::is_none));
+    assert_eq!(game.quantum.as_ref().unwrap().cycle, Some(1));
+}
+
+#[test]
+fn test_collapse_wins() {
+    let mut game = quantum();
+    for (first, second) in [(0, 1), (3, 4), (1, 2), (4, 5), (0, 2)] {
+        play_move(&mut game, Move::Spooky { first, second }).unwrap();
+    }
+    assert_eq!(game.outcome, None);
+    // every X mark in the cycle lands on the top row
+    play_move(&mut game, Move::Collapse(0)).unwrap();
+    assert_eq!(&game.board[..3], &[Some(Player::X); 3]);
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.quantum_scores(), Some([1.0, 0.0]));
+}
+
+#[test]
+fn test_simultaneous_lines() {
+    let mut game = quantum();
+    // X's row was finished by move 4 and O's by move 5, so X's counts first
+    let state = game.quantum.as_mut().unwrap();
+    for (cell, player, turn) in [(0, Player::X, 0), (3, Player::O, 1), (1, Player::X, 2), (4, Player::O, 3), (2, Player::X, 4), (5, Player::O, 5)] {
+        game.board[cell] = Some(player);
+        state.turns[cell] = Some(turn);
+    }
+    assert_eq!(game.quantum_scores(), Some([1.0, 0.5]));
+    assert_eq!(game.winner(), Some(Player::X));
+    // a mark finishing two lines at once scores double
+    let mut game = quantum();
+    let state = game.quantum.as_mut().unwrap();
+    for (cell, turn) in [(1, 0), (2, 2), (3, 4), (6, 6), (0, 8)] {
+        game.board[cell] = Some(Player::O);
+        state.turns[cell] = Some(turn);
+    }
+    assert_eq!(game.quantum_scores(), Some([0.0, 2.0]));
+    assert_eq!(game.winner(), Some(Player::O));
+}
+
+#[test]
+fn test_engine() {
+    let mut game = quantum();
+    let mut engine = MctsEngine::new(MctsConfig { iterations: Some(100), seed: 3, ..Default::default() });
+    while let Some(index) = engine.choose_move(&game) {
+        assert!(game.legal_moves().contains(&index));
+        game.play(index).unwrap();
+    }
+    assert!(game.is_over());
+}
@@ -0,0 +1,31 @@
+use crate::{render::{RenderStyle, Renderer}, Game, Player};
+
+fn game() -> Game {
+    let mut game = Game::new(Player::X);
+    for position in [0, 4, 8] {
+        game.move_player(&game.current_player.unwrap(), position).unwrap();
+    }
+    game
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(Player::O.to_string(), "O");
+    assert_eq!(game().to_string(), " X |   |   \n-----------\n   | O |   \n-----------\n   |   | X ");
+}
+
+#[test]
+fn test_styles() {
+    assert_eq!(Renderer::new(RenderStyle::Compact).render(&game()), "X../.O./..X");
+    assert_eq!(Renderer::new(RenderStyle::Unicode).render(&game()), "\
+┌───┬───┬───┐
+│ X │   │   │
+├───┼───┼───┤
+│   │ O │   │
+├───┼───┼───┤
+│   │   │ X │
+└───┴───┴───┘");
+    let wide = Game::with_size(4, 2, 3, Player::O).unwrap();
+    assert_eq!(Renderer::new(RenderStyle::Compact).render(&wide), "..../....");
+    assert_eq!(wide.to_string().lines().nth(1), Some("---------------"));
+}
@@ -0,0 +1,59 @@
+use crate::{analysis::{Evaluation, Solver}, setup::Setup, symmetry::Symmetry, zobrist, Game, Outcome, Player, Variant};
+
+fn puzzle(setup: Setup) -> Result<Game, &'static str> {
+    Game::from_setup(Variant::Standard, 3, 3, 3, 2, Player::X, setup)
+}
+
+#[test]
+fn test_blocked_cells() {
+    let mut game = puzzle(Setup { blocked: vec![4], marks: Vec::new() }).unwrap();
+    assert_eq!(game.legal_moves().len(), 8);
+    assert!(game.play(4).is_err());
+    assert!(game.is_blocked(4));
+    game.play(0).unwrap();
+    assert_eq!(game.undo(), Some((Player::X, 0)));
+    assert_eq!(game.undo(), None);
+    assert_eq!(game.to_string().lines().nth(2), Some("   | # |   "));
+    // with the centre gone neither player can win
+    assert_eq!(Solver::default().evaluate(&game), Evaluation::Draw);
+
+    // only the reflections that keep a blocked corner in place make positions equivalent
+    let mut game = puzzle(Setup { blocked: vec![0], marks: Vec::new() }).unwrap();
+    game.play(2).unwrap();
+    assert_eq!(game.canonical().symmetry, Symmetry::Transpose);
+}
+
+#[test]
+fn test_marks() {
+    let setup = Setup { blocked: Vec::new(), marks: vec![(0, Player::X), (4, Player::O), (1, Player::X), (6, Player::O)] };
+    let mut game = puzzle(setup.clone()).unwrap();
+    assert_eq!(game.board[4], Some(Player::O));
+    assert_eq!(game.hash, zobrist::hash(&game.board, game.current_player));
+    assert!(game.moves.is_empty());
+    game.play(2).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.setup, setup);
+}
+
+#[test]
+fn test_inconsistent_setups() {
+    let marks = |marks: &[(usize, Player)]| Setup { blocked: Vec::new(), marks: marks.to_vec() };
+    assert!(puzzle(marks(&[(9, Player::X)])).is_err());
+    assert!(puzzle(Setup { blocked: vec![0], marks: vec![(0, Player::X)] }).is_err());
+    assert!(puzzle(marks(&[(0, Player::X), (1, Player::X)])).is_err());
+    assert!(puzzle(marks(&[(0, Player(2))])).is_err());
+    assert!(puzzle(marks(&[(0, Player::X), (1, Player::X), (2, Player::X), (3, Player::O), (4, Player::O), (8, Player::O)])).is_err());
+    assert!(puzzle(Setup { blocked: (0..9).collect(), marks: Vec::new() }).is_err());
+    let blocked = Setup { blocked: vec![4], marks: Vec::new() };
+    assert!(Game::from_setup(Variant::Gravity, 3, 3, 3, 2, Player::X, blocked).is_err());
+}
+
+#[test]
+fn test_random_layout() {
+    let layout = Setup::random(5, 5, 4, 7);
+    assert_eq!(layout, Setup::random(5, 5, 4, 7));
+    assert_eq!(layout.blocked.len(), 4);
+    // the layout looks the same from both sides of the board
+    assert!(layout.blocked.iter().all(|cell| layout.blocked.contains(&(24 - cell))));
+    assert_eq!(Setup::random(5, 5, 3, 7).blocked.len(), 3);
+}
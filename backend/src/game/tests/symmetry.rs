@@ -0,0 +1,45 @@
+use crate::{symmetry::Symmetry, zobrist, Game, Player};
+use super::play;
+
+#[test]
+fn test_incremental_hash() {
+    let mut game = Game::with_size(5, 4, 4, Player::O).unwrap();
+    for position in [0, 7, 19, 12, 3] {
+        game.play(position).unwrap();
+        assert_eq!(game.hash, zobrist::hash(&game.board, game.current_player));
+    }
+    // the same marks reached in another order hash the same
+    assert_eq!(play(&[0, 4, 8]).hash, play(&[8, 4, 0]).hash);
+    assert_ne!(play(&[0, 4]).hash, play(&[4, 0]).hash);
+    let mut forfeited = play(&[0]);
+    forfeited.forfeit(&Player::O);
+    assert_eq!(forfeited.hash, zobrist::hash(&forfeited.board, None));
+}
+
+#[test]
+fn test_symmetries() {
+    for symmetry in Symmetry::ALL {
+        for position in 0..9 {
+            assert_eq!(symmetry.inverse().apply(symmetry.apply(position, 3, 3), 3, 3), position);
+        }
+    }
+    assert_eq!(Symmetry::Rotate90.apply(0, 3, 3), 2);
+    assert_eq!(Symmetry::Transpose.apply(1, 3, 3), 3);
+    assert_eq!(Symmetry::FlipHorizontal.apply(3, 4, 2), 0);
+    assert_eq!(Symmetry::of(4, 2).len(), 4);
+}
+
+#[test]
+fn test_canonical() {
+    // all four corner openings are the same position
+    let corners: Vec<_> = [0, 2, 6, 8].iter().map(|corner| play(&[*corner]).canonical()).collect();
+    assert!(corners.iter().all(|canonical| canonical.board == corners[0].board && canonical.hash == corners[0].hash));
+    assert_ne!(play(&[4]).canonical().hash, corners[0].hash);
+
+    // a move in the canonical board maps back to the equivalent move in the original
+    let game = play(&[8, 4]);
+    let canonical = game.canonical();
+    let position = canonical.symmetry.inverse().apply(0, 3, 3);
+    assert_eq!(canonical.board[0], game.board[position]);
+    assert_eq!(canonical.symmetry.transform(&game.board, 3, 3), canonical.board);
+}
@@ -0,0 +1,50 @@
+use crate::{analysis::{analyze, Evaluation, Solver}, setup::Setup, tablebase::Tablebase, Game, Player, Variant};
+
+#[test]
+fn test_known_facts() {
+    let tablebase = Tablebase::generate();
+    assert_eq!(tablebase.positions(), 5478);
+    let empty = tablebase.get(&Game::new(Player::X)).unwrap();
+    assert_eq!(empty.value, Evaluation::Draw);
+
+    // perfect play from both sides draws, whoever starts
+    for starter in [Player::X, Player::O] {
+        let mut game = Game::new(starter);
+        while let Some(position) = tablebase.best_move(&game) {
+            game.play(position).unwrap();
+        }
+        assert!(game.is_over());
+        assert_eq!(game.winner(), None);
+    }
+}
+
+#[test]
+fn test_matches_solver() {
+    let tablebase = Tablebase::generate();
+    let mut solver = Solver::default();
+    let mut game = Game::new(Player::O);
+    for position in [4, 0, 8] {
+        game.play(position).unwrap();
+        assert_eq!(tablebase.get(&game).unwrap().value, solver.evaluate(&game));
+        assert_eq!(tablebase.analyze(&game), Some(analyze(&game)));
+    }
+    assert_eq!(tablebase.get(&Game::with_size(4, 4, 3, Player::X).unwrap()), None);
+    // prepared marks change which positions can be reached, so those games aren't looked up either
+    let setup = Setup { blocked: Vec::new(), marks: vec![(0, Player::X), (4, Player::O)] };
+    let prepared = Game::from_setup(Variant::Standard, 3, 3, 3, 2, Player::X, setup).unwrap();
+    assert_eq!(tablebase.get(&prepared), None);
+}
+
+#[test]
+fn test_export_import() {
+    let tablebase = Tablebase::generate();
+    let bytes = tablebase.to_bytes();
+    assert_eq!(Tablebase::from_bytes(&bytes), Ok(tablebase.clone()));
+    assert!(Tablebase::from_bytes(&bytes[..100]).is_err());
+    assert!(Tablebase::from_bytes(b"not a tablebase").is_err());
+
+    let path = std::env::temp_dir().join(format!("tablebase-{}.bin", std::process::id()));
+    tablebase.save(&path).unwrap();
+    assert_eq!(Tablebase::load(&path).unwrap(), tablebase);
+    std::fs::remove_file(path).unwrap();
+}
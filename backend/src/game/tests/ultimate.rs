@@ -0,0 +1,91 @@
+use crate::{engine::Engine, mcts::{MctsConfig, MctsEngine}, ultimate::{position, split}, Game, Outcome, Player, Variant};
+
+/// Plays moves given as (sub-board, cell) pairs.
+fn play_sub_boards(game: &mut Game, moves: &[(usize, usize)]) {
+    for (board, cell) in moves {
+        game.play(position(*board, *cell)).unwrap();
+    }
+}
+
+#[test]
+fn test_positions() {
+    assert_eq!(position(0, 0), 0);
+    assert_eq!(position(4, 4), 40);
+    assert_eq!(position(2, 3), 15);
+    assert_eq!(position(8, 8), 80);
+    for index in 0..81 {
+        let (board, cell) = split(index);
+        assert_eq!(position(board, cell), index);
+    }
+}
+
+#[test]
+fn test_sent_to_sub_board() {
+    let mut game = Game::ultimate(Player::X);
+    assert_eq!(game.legal_moves().len(), 81);
+    play_sub_boards(&mut game, &[(4, 2)]);
+    // O has to answer in sub-board 2
+    assert_eq!(game.next_board(), Some(2));
+    assert_eq!(game.legal_moves(), (0..9).map(|cell| position(2, cell)).collect::<Vec<_>>());
+    assert!(game.play(position(3, 0)).is_err());
+    assert!(game.play(position(2, 0)).is_ok());
+}
+
+#[test]
+fn test_closed_sub_board_frees_choice() {
+    let mut game = Game::ultimate(Player::X);
+    // X wins sub-board 0 along its top row while O keeps sending X back there
+    play_sub_boards(&mut game, &[(0, 4), (4, 0), (0, 3), (3, 0), (0, 5)]);
+    assert_eq!(game.sub_board_winner(0), Some(Player::X));
+    assert!(game.is_closed(0));
+    assert_eq!(game.outcome, None);
+    // O is sent to sub-board 5, then back into the closed sub-board 0, which frees X's choice
+    play_sub_boards(&mut game, &[(5, 0)]);
+    assert_eq!(game.next_board(), None);
+    assert!(game.play(position(0, 0)).is_err());
+    assert!(game.legal_moves().iter().all(|position| split(*position).0 != 0));
+    assert_eq!(game.legal_moves().len(), 81 - 9 - 3);
+}
+
+#[test]
+fn test_meta_win() {
+    let mut game = Game::ultimate(Player::X);
+    // X takes the middle rows of sub-boards 0, 1 and 2, and O keeps sending X to the next one it needs
+    play_sub_boards(&mut game, &[
+        (0, 3), (3, 1), (1, 4), (4, 2), (2, 5), (5, 0), (0, 4), (4, 1), (1, 5),
+        (5, 2), (2, 3), (3, 0), (0, 5),
+    ]);
+    assert_eq!(game.sub_board_winner(0), Some(Player::X));
+    play_sub_boards(&mut game, &[(5, 1), (1, 3)]);
+    assert_eq!(game.sub_board_winner(1), Some(Player::X));
+    assert_eq!(game.sub_board_winner(5), Some(Player::O));
+    play_sub_boards(&mut game, &[(3, 2)]);
+    assert_eq!(game.outcome, None);
+    play_sub_boards(&mut game, &[(2, 4)]);
+    assert_eq!(game.sub_board_winner(2), Some(Player::X));
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::X)));
+    assert_eq!(game.winner(), Some(Player::X));
+}
+
+#[test]
+fn test_sizes() {
+    assert!(Game::with_variant(Variant::Ultimate, 3, 3, 3, Player::X).is_err());
+    // lines don't run across sub-boards
+    let mut game = Game::ultimate(Player::X);
+    for position in [1, 2, 3] {
+        game.board[position] = Some(Player::X);
+    }
+    assert!(!game.completes_line(2));
+    assert_eq!(game.sub_board_winner(0), None);
+    // where the next move has to go isn't on the board, so the solver can't tell positions apart
+    assert!(!Variant::Ultimate.is_searchable());
+}
+
+#[test]
+fn test_engines_follow_the_rules() {
+    let mut game = Game::ultimate(Player::X);
+    let mut engine = MctsEngine::new(MctsConfig { iterations: Some(200), ..Default::default() });
+    play_sub_boards(&mut game, &[(4, 2)]);
+    let position = engine.choose_move(&game).unwrap();
+    assert_eq!(split(position).0, 2);
+}
@@ -0,0 +1,47 @@
+use crate::{analysis::{Evaluation, Solver}, zobrist, Game, Move, Outcome, Player, Variant};
+
+fn wild() -> Game {
+    Game::with_variant(Variant::Wild, 3, 3, 3, Player::X).unwrap()
+}
+
+#[test]
+fn test_choosing_symbols() {
+    let mut game = wild();
+    assert_eq!(game.legal_moves().len(), 18);
+    game.move_symbol(&Player::X, 4, Player::O).unwrap();
+    assert_eq!(game.board[4], Some(Player::O));
+    assert_eq!(game.current_player, Some(Player::O));
+    assert!(game.move_symbol(&Player::X, 0, Player::X).is_err());
+    assert!(game.move_symbol(&Player::O, 0, Player(2)).is_err());
+    // a plain position still places the mover's own symbol
+    game.play(0).unwrap();
+    assert_eq!(game.board[0], Some(Player::O));
+    assert_eq!(game.hash, zobrist::hash(&game.board, game.current_player));
+    assert_eq!(game.undo(), Some((Player::O, 0)));
+    assert_eq!(game.undo(), Some((Player::X, game.encode(Move::Mark { position: 4, symbol: Player::O }))));
+    assert_eq!(game.hash, wild().hash);
+    // other variants only let players place their own symbol
+    let mut game = Game::new(Player::X);
+    assert!(game.move_symbol(&Player::X, 4, Player::O).is_err());
+    assert!(game.move_symbol(&Player::X, 4, Player::X).is_ok());
+}
+
+#[test]
+fn test_any_line_wins() {
+    let mut game = wild();
+    game.move_symbol(&Player::X, 0, Player::O).unwrap();
+    game.move_symbol(&Player::O, 8, Player::X).unwrap();
+    game.move_symbol(&Player::X, 1, Player::O).unwrap();
+    assert_eq!(game.outcome, None);
+    // O completes a row of Os that X started, and the win is O's
+    game.move_symbol(&Player::O, 2, Player::O).unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Win(Player::O)));
+    assert_eq!(game.winner(), Some(Player::O));
+}
+
+#[test]
+fn test_solver() {
+    // the first player can always force a win in wild tic-tac-toe
+    let game = wild();
+    assert!(matches!(Solver::default().evaluate(&game), Evaluation::Win(_)));
+}
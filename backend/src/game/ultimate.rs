@@ -0,0 +1,73 @@
+//! Ultimate tic-tac-toe: nine 3x3 sub-boards laid out in a 3x3 grid. Each move sends the opponent to the
+//! sub-board matching the cell just played, unless that sub-board is closed, then they can play anywhere.
+//! Winning a sub-board claims it, three claimed sub-boards in a row win the game.
+//!
+//! The board is stored like any other 9x9 board, row by row, so positions here convert between that
+//! and a sub-board and a cell within it, both numbered 0 to 8 row by row.
+use crate::{Game, Player};
+
+/// The width and height of the whole board.
+pub const SIZE: usize = 9;
+
+/// The rows, columns and diagonals of a 3x3 grid.
+pub(crate) const LINES: [[usize; 3]; 8] = [[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8], [0, 4, 8], [2, 4, 6]];
+
+/// The board position of `cell` within sub-board `board`.
+pub fn position(board: usize, cell: usize) -> usize {
+    (board / 3 * 3 + cell / 3) * SIZE + board % 3 * 3 + cell % 3
+}
+
+/// The sub-board a position is in and the cell within it.
+pub fn split(position: usize) -> (usize, usize) {
+    let (row, column) = (position / SIZE, position % SIZE);
+    (row / 3 * 3 + column / 3, row % 3 * 3 + column % 3)
+}
+
+/// The board positions making up a sub-board.
+pub fn cells(board: usize) -> impl Iterator<Item = usize> {
+    (0..9).map(move |cell| position(board, cell))
+}
+
+impl Game {
+    /// The player who won a sub-board, if anyone has.
+    pub fn sub_board_winner(&self, board: usize) -> Option<Player> {
+        cells(board).find_map(|position| {
+            let player = self.board[position]?;
+            self.completes_line(position).then_some(player)
+        })
+    }
+    /// Whether a sub-board is won or full, so nobody can play in it any more.
+    pub fn is_closed(&self, board: usize) -> bool {
+        self.sub_board_winner(board).is_some() || cells(board).all(|position| self.board[position].is_some())
+    }
+    /// The sub-board the player to move has to play in, None when they can choose any open one.
+    pub fn next_board(&self) -> Option<usize> {
+        let (_, last) = self.moves.last()?;
+        let (_, cell) = split(*last);
+        (!self.is_closed(cell)).then_some(cell)
+    }
+    /// The player with three sub-boards in a row.
+    pub(crate) fn meta_winner(&self) -> Option<Player> {
+        let winners: Vec<Option<Player>> = (0..9).map(|board| self.sub_board_winner(board)).collect();
+        LINES.iter().find_map(|line| {
+            let player = winners[line[0]]?;
+            line.iter().all(|board| winners[*board] == Some(player)).then_some(player)
+        })
+    }
+    /// Checks the ultimate rules for a move to an empty cell.
+    pub(crate) fn check_ultimate(&self, position: usize) -> Result<(), &'static str> {
+        let (board, _) = split(position);
+        if self.is_closed(board) { return Err("That sub-board is already decided"); }
+        if self.next_board().is_some_and(|next| next != board) {
+            return Err("You have to play in the sub-board your opponent sent you to");
+        }
+        Ok(())
+    }
+    pub(crate) fn ultimate_moves(&self) -> Vec<usize> {
+        let boards: Vec<usize> = match self.next_board() {
+            Some(board) => vec![board],
+            None => (0..9).filter(|board| !self.is_closed(*board)).collect(),
+        };
+        boards.into_iter().flat_map(cells).filter(|position| self.board[*position].is_none()).collect()
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::OnceLock;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::{Player, MAX_BOARD_SIZE, MAX_PLAYERS};
+
+/// Random keys for every (cell, player) pair, plus one for each player but X being the player to move.
+/// The seed is fixed so hashes are the same on every run and can be stored.
+struct Keys {
+    cells: Vec<[u64; MAX_PLAYERS]>,
+    to_move: [u64; MAX_PLAYERS],
+}
+
+fn keys() -> &'static Keys {
+    static KEYS: OnceLock<Keys> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let mut rng = StdRng::seed_from_u64(0x71c7ac70e);
+        let mut cells: Vec<[u64; MAX_PLAYERS]> = (0..MAX_BOARD_SIZE * MAX_BOARD_SIZE)
+            .map(|_| [rng.gen(), rng.gen(), 0, 0])
+            .collect();
+        let mut to_move = [0, rng.gen(), 0, 0];
+        // keys for the players after X and O come last, so two-player hashes stay as they were
+        for keys in &mut cells {
+            keys[2..].iter_mut().for_each(|key| *key = rng.gen());
+        }
+        to_move[2..].iter_mut().for_each(|key| *key = rng.gen());
+        Keys { cells, to_move }
+    })
+}
+
+/// The key toggled in and out of the hash when `player` places or removes a mark at `position`.
+pub fn cell_key(position: usize, player: Player) -> u64 {
+    keys().cells[position][player.index()]
+}
+
+/// The key for the player to move. X to move has none, so a finished game hashes by its board alone.
+pub fn turn_key(player: Option<Player>) -> u64 {
+    player.map_or(0, |player| keys().to_move[player.index()])
+}
+
+/// Hashes a position from scratch. `Game` keeps its hash up to date move by move instead.
+pub fn hash(board: &[Option<Player>], to_move: Option<Player>) -> u64 {
+    board.iter().enumerate()
+        .filter_map(|(position, cell)| cell.map(|player| cell_key(position, player)))
+        .fold(turn_key(to_move), |hash, key| hash ^ key)
+}
@@ -1,36 +1,135 @@
-use std::sync::{Arc, Mutex};
+use std::{fmt, sync::{Arc, Mutex}, time::{Duration, Instant}};
 use game::{Game, Player};
-use rand::{thread_rng, Rng};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde::{ser::SerializeStruct, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use crate::series::{Series, SeriesMode};
 use crate::session::Session;
+use crate::settings::{random_player, LobbySettings, SymbolAssignment};
 
 #[derive(Clone, Debug)]
 pub struct Lobby {
     pub code: String,
+    pub host: String,                   // access token of the session that owns the lobby
     pub game: Option<Game>,
-    pub players: [Option<(Arc<Mutex<Session>>, Player)>; 2]
+    pub game_id: Option<String>,        // identifies the current game, e.g. for looking up its review
+    pub players: Vec<Option<(Arc<Mutex<Session>>, Player)>>, // one seat per player the settings allow
+    pub password: Option<PasswordHash>, // when set, joining requires the password or a valid invite
+    pub invites: Vec<Invite>,
+    pub entrants: Vec<String>,          // access tokens that get in without credentials, e.g. tournament players
+    pub settings: LobbySettings,
+    pub games_played: usize,
+    pub series: Option<Series>,
+}
+
+/// A lobby password as it's kept: salted and hashed, so the server never holds on to the password itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PasswordHash {
+    salt: [u8; 16],
+    digest: [u8; 32],
+}
+
+impl PasswordHash {
+    pub fn new(password: &str) -> Self {
+        let salt: [u8; 16] = thread_rng().gen();
+        PasswordHash { salt, digest: Self::digest(&salt, password) }
+    }
+    fn digest(salt: &[u8], password: &str) -> [u8; 32] {
+        Sha256::new().chain_update(salt).chain_update(password.as_bytes()).finalize().into()
+    }
+    /// Compares every byte whatever the others hold, so how long a guess takes says nothing about the password.
+    pub fn verify(&self, password: &str) -> bool {
+        Self::digest(&self.salt, password).iter().zip(self.digest)
+            .fold(0, |difference, (a, b)| difference | (a ^ b)) == 0
+    }
+}
+
+/// A token generated by the host that lets a session join a private lobby without the password.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Invite {
+    pub token: String,
+    pub single_use: bool,
+    #[serde(skip)]
+    pub expires_at: Option<Instant>,
+}
+
+impl Invite {
+    pub fn new(single_use: bool, lifetime: Option<Duration>) -> Self {
+        let token: String = thread_rng().sample_iter(Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect();
+        Invite { token, single_use, expires_at: lifetime.map(|lifetime| Instant::now() + lifetime) }
+    }
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| Instant::now() >= expires_at)
+    }
+}
+
+/// Reasons a session can be refused entry to a lobby, sent to the client as an error code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinError {
+    LobbyNotFound,
+    LobbyFull,
+    PasswordRequired,
+    InvalidPassword,
+    InvalidInvite,
+    InviteExpired,
+    TooManyAttempts,
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
 }
 
 impl Serialize for Lobby {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: Serializer {
         #[derive(Serialize)]
-        struct SerializedPlayer { port: u16, symbol: String }
+        struct SerializedPlayer { port: u16, symbol: Player, mark: String, role: Option<&'static str> }
         let p: Vec<Option<SerializedPlayer>> = self.players.iter().map(|player|
             if let Some((session, player)) = player {
                 let session = session.lock().unwrap();
                 Some(SerializedPlayer {
                     port: session.socket.port(),
-                    symbol: match player {
-                        Player::X => "X".to_string(),
-                        Player::O => "O".to_string(),
-                    }
+                    symbol: *player,
+                    mark: self.settings.mark(*player),
+                    role: self.settings.variant.role(*player),
                 })
             } else { None }
         ).collect();
+        #[derive(Serialize)]
+        struct SerializedSeries { mode: SeriesMode, scores: Vec<u8>, points: Vec<f32>, draws: u8, winner: Option<u16>, finished: bool }
+        let series = self.series.as_ref().map(|series| {
+            let players: Vec<(String, u16)> = self.players.iter().flatten().map(|(session, _)| {
+                let session = session.lock().unwrap();
+                (session.access_token.clone(), session.socket.port())
+            }).collect();
+            SerializedSeries {
+                mode: series.mode,
+                scores: players.iter().map(|(token, _)| series.score(token)).collect(),
+                points: players.iter().map(|(token, _)| series.points(token)).collect(),
+                draws: series.draws,
+                winner: players.iter()
+                    .find(|(token, _)| series.winner.as_ref() == Some(token))
+                    .map(|(_, port)| *port),
+                finished: series.finished,
+            }
+        });
         let mut s = serializer.serialize_struct("Lobby", 3)?;
         s.serialize_field("code", &self.code)?;
         s.serialize_field("players", &p)?;
+        s.serialize_field("private", &self.is_private())?;
+        s.serialize_field("game_id", &self.game_id)?;
+        s.serialize_field("outcome", &self.game.as_ref().and_then(|game| game.outcome))?;
+        // quantum games are scored by X and by O rather than just won
+        s.serialize_field("scores", &self.game.as_ref().and_then(|game| game.quantum_scores()))?;
+        // the position the current game started from, which for random layouts only the server knows
+        s.serialize_field("setup", &self.game.as_ref().map(|game| &game.setup))?;
+        s.serialize_field("settings", &self.settings)?;
+        s.serialize_field("series", &series)?;
         s.end()
     }
 }
@@ -47,24 +146,154 @@ impl Lobby {
             .map(|_| thread_rng().gen_range(0..10))
             .map(|n| n.to_string())
             .collect::<String>();
-        let mut lobby = Lobby { code, game: None, players: [None, None] };
+        let host = initiator.lock().unwrap().access_token.clone();
+        let mut lobby = Lobby {
+            code, host, game: None, game_id: None, players: vec![None; 2], password: None, invites: Vec::new(), entrants: Vec::new(),
+            settings: LobbySettings::default(), games_played: 0, series: None
+        };
         lobby.add_player(initiator);
         lobby
     }
+    pub fn is_host(&self, session: &Session) -> bool {
+        self.host == session.access_token
+    }
+    pub fn is_private(&self) -> bool {
+        self.password.is_some()
+    }
+    pub fn set_password(&mut self, password: Option<String>) -> &mut Self {
+        self.password = password.filter(|password| !password.is_empty()).map(|password| PasswordHash::new(&password));
+        self
+    }
+    pub fn create_invite(&mut self, single_use: bool, lifetime: Option<Duration>) -> Invite {
+        self.invites.retain(|invite| !invite.is_expired());
+        let invite = Invite::new(single_use, lifetime);
+        self.invites.push(invite.clone());
+        invite
+    }
+    /// Checks the credentials a session supplied when joining. A matching invite is accepted
+    /// even when a password is set, and single-use invites are consumed on success.
+    pub fn authorize(&mut self, password: Option<&str>, invite: Option<&str>) -> Result<(), JoinError> {
+        if let Some(token) = invite {
+            let index = self.invites.iter()
+                .position(|invite| invite.token == token)
+                .ok_or(JoinError::InvalidInvite)?;
+            if self.invites[index].is_expired() {
+                self.invites.remove(index);
+                return Err(JoinError::InviteExpired);
+            }
+            if self.invites[index].single_use {
+                self.invites.remove(index);
+            }
+            return Ok(());
+        }
+        match (&self.password, password) {
+            (None, _) => Ok(()),
+            (Some(_), None) => Err(JoinError::PasswordRequired),
+            (Some(expected), Some(given)) if expected.verify(given) => Ok(()),
+            (Some(_), Some(_)) => Err(JoinError::InvalidPassword),
+        }
+    }
     pub fn start_game(&mut self) {
-        if self.player_count() == 2 {
-            self.game = Some(Game::new());
+        if self.players.iter().all(Option::is_some) {
+            let series_over = self.series.as_ref().is_none_or(|series| series.finished);
+            if self.settings.series != SeriesMode::Single && series_over {
+                self.series = Some(Series::new(self.settings.series));
+            }
+            if self.settings.symbols == SymbolAssignment::Alternate && self.games_played > 0 {
+                self.swap_symbols();
+            }
+            // within a series the players take turns making the first move
+            let seats = self.players.len();
+            let last_starter = self.series.as_ref().and_then(|series| series.last_starter.clone());
+            let starting_player = last_starter
+                .and_then(|token| self.players.iter().flatten()
+                    .find(|(session, _)| session.lock().unwrap().access_token == token)
+                    .map(|(_, player)| Player(((player.index() + 1) % seats) as u8)))
+                .unwrap_or_else(|| self.settings.starting_player.for_game(self.games_played, seats));
+            let starter = self.token_for(starting_player);
+            if let Some(series) = self.series.as_mut() {
+                series.last_starter = starter;
+            }
+            self.game = self.settings.new_game(starting_player).ok();
+            self.game_id = Some(thread_rng().sample_iter(Alphanumeric).take(8).map(char::from).collect());
+            self.games_played += 1;
         }
     }
+    /// Records a finished game in the lobby's series and starts the next game if the series
+    /// hasn't been decided yet. Returns whether a new game was started.
+    pub fn finish_game(&mut self) -> bool {
+        let Some(outcome) = self.game.as_ref().and_then(|game| game.outcome) else { return false; };
+        let winner = outcome.winner().and_then(|player| self.token_for(player));
+        let points: Vec<(String, f32)> = self.game.as_ref().and_then(|game| game.quantum_scores()).into_iter()
+            .flat_map(|scores| [Player::X, Player::O].into_iter().zip(scores))
+            .filter_map(|(player, points)| Some((self.token_for(player)?, points)))
+            .collect();
+        let Some(series) = self.series.as_mut() else { return false; };
+        for (token, points) in points {
+            series.add_points(&token, points);
+        }
+        if series.record(winner.as_deref()).finished { return false; }
+        self.start_game();
+        true
+    }
+    /// The access token of the session playing as the given symbol.
+    pub fn token_for(&self, symbol: Player) -> Option<String> {
+        self.players.iter().flatten()
+            .find(|(_, player)| *player == symbol)
+            .map(|(session, _)| session.lock().unwrap().access_token.clone())
+    }
+    pub fn in_game(&self) -> bool {
+        self.game.as_ref().is_some_and(|game| !game.is_over())
+    }
+    /// Replaces the lobby's settings, resizing the seats to the new player count and handing the host
+    /// their chosen symbol straight away. The caller makes sure nobody is left without a seat.
+    pub fn set_settings(&mut self, settings: LobbySettings) -> &mut Self {
+        let (seats, symbols) = (settings.players, settings.symbols);
+        self.settings = settings;
+        let mut seated: Vec<(Arc<Mutex<Session>>, Player)> = self.players.iter().flatten().cloned().collect();
+        seated.truncate(seats);
+        self.players = vec![None; seats];
+        for (seat, (session, player)) in seated.into_iter().enumerate() {
+            let symbol = match symbols {
+                SymbolAssignment::Host(symbol) if session.lock().unwrap().access_token == self.host => symbol,
+                SymbolAssignment::Host(_) => self.free_symbol(),
+                _ if player.index() < seats && !self.has_symbol(player) => player,
+                _ => self.free_symbol(),
+            };
+            self.players[seat] = Some((session, symbol));
+        }
+        self
+    }
+    /// Hands every player the symbol after theirs, which is a plain swap between two players.
+    pub fn swap_symbols(&mut self) -> &mut Self {
+        let seats = self.players.len();
+        for (_, player) in self.players.iter_mut().flatten() {
+            *player = Player(((player.index() + 1) % seats) as u8);
+        }
+        self
+    }
+    fn has_symbol(&self, symbol: Player) -> bool {
+        self.players.iter().flatten().any(|(_, player)| *player == symbol)
+    }
+    /// The first symbol nobody in the lobby is playing as, skipping the one the host has asked for.
+    fn free_symbol(&self) -> Player {
+        let reserved = match self.settings.symbols {
+            SymbolAssignment::Host(symbol) => Some(symbol),
+            _ => None,
+        };
+        Player::all(self.players.len())
+            .find(|symbol| Some(*symbol) != reserved && !self.has_symbol(*symbol))
+            .unwrap_or(Player::X)
+    }
     pub fn add_player(&mut self, player: Arc<Mutex<Session>>) -> &mut Self {
-        let index: usize = self.players[0].is_some() as usize;
-        self.players[index] = Some((player, match self.players[0] {
-            Some((_, player)) => match player {
-                Player::X => Player::O,
-                Player::O => Player::X,
-            },
-            None => [Player::X, Player::O][thread_rng().gen_bool(0.5) as usize]
-        }));
+        let Some(index) = self.players.iter().position(Option::is_none) else { return self; };
+        let symbol = match self.settings.symbols {
+            SymbolAssignment::Host(symbol) if self.is_host(&player.lock().unwrap()) => symbol,
+            _ if self.has_players() => self.free_symbol(),
+            SymbolAssignment::Host(_) => self.free_symbol(),
+            _ => random_player(self.players.len()),
+        };
+        self.players[index] = Some((player, symbol));
         self
     }
     pub fn has_player(&self, session: Arc<Mutex<Session>>) -> bool {
@@ -74,7 +303,15 @@ impl Lobby {
             } else { false }
         })
     }
+    /// Removes the session from the lobby. Leaving a game or series in progress forfeits it,
+    /// and the host role passes to whoever is left.
     pub fn remove_player(&mut self, player: Arc<Mutex<Session>>) -> &mut Self {
+        let symbol = self.players.iter().flatten()
+            .find(|(session, _)| Arc::ptr_eq(session, &player))
+            .map(|(_, symbol)| *symbol);
+        if let (Some(game), Some(symbol)) = (self.game.as_mut(), symbol) {
+            game.forfeit(&symbol);
+        }
         let mut players: Vec<Option<(Arc<Mutex<Session>>, Player)>> = self.players
             .iter()
             .filter_map(|entry| {
@@ -85,9 +322,20 @@ impl Lobby {
                 } else { None }
             })
             .collect();
-        let diff = self.players.len() - players.len();
-        for _ in 0..(diff) { players.push(None); }
-        self.players.clone_from_slice(&players[0..]);
+        players.resize(self.players.len(), None);
+        self.players = players;
+        let remaining: Vec<String> = self.players.iter().flatten()
+            .map(|(session, _)| session.lock().unwrap().access_token.clone())
+            .collect();
+        if let Some(first) = remaining.first() {
+            if self.host == player.lock().unwrap().access_token {
+                self.host = first.clone();
+            }
+        }
+        // a player walking out of a series hands it to their opponent once nobody else is left
+        if let ([remaining], Some(series)) = (&remaining[..], self.series.as_mut()) {
+            series.concede(remaining);
+        }
         self
     }
     pub fn has_players(&self) -> bool {
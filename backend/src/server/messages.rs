@@ -2,11 +2,15 @@ use std::sync::{Arc, MutexGuard};
 use serde_json::{json, Value};
 use std::sync::Mutex;
 use std::net::SocketAddr;
-use game::Player;
+use std::time::Duration;
+use game::{analysis::{analyze, Analysis}, ultimate, Game, Move, Player, Variant};
 use serde::Deserialize;
 use crate::lobby::Lobby;
+use crate::review::GameReview;
 use crate::session::Session;
+use crate::settings::LobbySettings;
 use crate::state::AppState;
+use crate::tournament::TournamentFormat;
 
 #[derive(Debug, Deserialize, Eq, Hash, PartialEq)]
 #[serde(tag = "type", content = "data")]
@@ -15,10 +19,67 @@ pub enum ClientMessage { // these are messages that should be received by the co
     Connection { nickname: Option<String>, access_token: Option<String> },
     Nickname { nickname: String },          // changes the nickname of the current session
     CreateLobby,                            // creates a new lobby for the current session
-    JoinLobby { code: String },             // moves the current session to an existing lobby
+    // moves the current session to an existing lobby, private lobbies need the password or an invite token
+    JoinLobby { code: String, password: Option<String>, invite: Option<String> },
+    SetPassword { password: Option<String> }, // host only: makes the lobby private, or public again with None
+    // host only: generates an invite token, optionally single-use and/or expiring after a number of seconds
+    CreateInvite { #[serde(default)] single_use: bool, expires_in: Option<u64> },
+    LeaveLobby,                             // removes the current session from its lobby, forfeiting a game in progress
+    UpdateSettings(LobbySettings),          // host only: changes symbol assignment, the starting player and series mode
     StartGame,
-    Move { position: usize },                // move the session to a spot in their game
-    OnHover { position: usize }                // made when a player's mouse is hovered over a square
+    CreateTournament { format: TournamentFormat }, // creates a tournament and registers the current session for it
+    JoinTournament { id: String },          // registers the current session for a tournament that hasn't started
+    StartTournament { id: String },         // host only: draws up the schedule and starts the first round
+    TournamentStandings { id: String },     // the tournament's rounds, results and standings
+    // move the session to a spot in their game, placing `symbol` instead of their own in wild games
+    Move { position: Position, #[serde(default)] symbol: Option<Player> },
+    OnHover { position: Position },               // made when a player's mouse is hovered over a square
+    RequestHint,                            // analysis of the current position, if the lobby allows hints
+}
+
+/// Where a move goes: a cell of the board, a board and a cell within it in ultimate tic-tac-toe or
+/// Notakto, a column to drop a mark in when gravity is on, a piece to move in morris, or a spooky mark
+/// or the cell to collapse a cycle into in quantum games.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(untagged)]
+pub enum Position {
+    Cell(usize),
+    Nested { board: usize, cell: usize },
+    Column { column: usize },
+    Slide { from: usize, to: usize },
+    Spooky { cells: [usize; 2] },
+    Collapse { collapse: usize },
+}
+
+impl Position {
+    /// The cell on the board of `game`, or the encoded move for anything else. Only ultimate games
+    /// and Notakto have boards to address, and with gravity any cell or column means the cell a mark
+    /// dropped in that column lands on.
+    pub fn resolve(&self, game: &Game) -> Result<usize, String> {
+        match *self {
+            Position::Cell(position) if game.variant == Variant::Gravity && position < game.board.len()
+                => Position::Column { column: position % game.width }.resolve(game),
+            Position::Cell(position) => Ok(position),
+            Position::Column { .. } if game.variant != Variant::Gravity => Err("this game doesn't drop marks in columns".to_owned()),
+            Position::Column { column } if column < game.width => game.landing(column).ok_or("that column is full".to_owned()),
+            Position::Column { .. } => Err("column is out of bounds".to_owned()),
+            Position::Nested { board, cell } if game.variant == Variant::Notakto && board < game.boards() && cell < 9
+                => Ok(game.board_position(board, cell)),
+            Position::Nested { .. } if game.variant == Variant::Notakto => Err("that board doesn't exist".to_owned()),
+            Position::Nested { .. } if game.variant != Variant::Ultimate => Err("this game doesn't have sub-boards".to_owned()),
+            Position::Nested { board, cell } if board < 9 && cell < 9 => Ok(ultimate::position(board, cell)),
+            Position::Nested { .. } => Err("sub-boards and their cells are numbered 0 to 8".to_owned()),
+            Position::Slide { .. } if !matches!(game.variant, Variant::Morris { .. }) => Err("this game doesn't move pieces".to_owned()),
+            Position::Slide { from, to } if from < game.board.len() && to < game.board.len() => Ok(game.encode(Move::Slide { from, to })),
+            Position::Slide { .. } => Err("position is out of bounds".to_owned()),
+            Position::Spooky { .. } | Position::Collapse { .. } if game.variant != Variant::Quantum
+                => Err("this game isn't quantum".to_owned()),
+            Position::Spooky { cells: [first, second] } if first.max(second) < game.board.len()
+                => Ok(game.encode(Move::Spooky { first, second })),
+            Position::Collapse { collapse } if collapse < game.board.len() => Ok(game.encode(Move::Collapse(collapse))),
+            Position::Spooky { .. } | Position::Collapse { .. } => Err("position is out of bounds".to_owned()),
+        }
+    }
 }
 
 impl ClientMessage {
@@ -30,10 +91,21 @@ impl ClientMessage {
                 => ClientMessage::new_connection(state, socket, nickname, access_token),
             ClientMessage::Nickname { nickname } => ClientMessage::change_nickname(state, socket, nickname),
             ClientMessage::CreateLobby => ClientMessage::create_lobby(state, socket),
-            ClientMessage::JoinLobby { code } => ClientMessage::join_lobby(state, socket, code),
+            ClientMessage::JoinLobby { code, password, invite }
+                => ClientMessage::join_lobby(state, socket, code, password, invite),
+            ClientMessage::LeaveLobby => ClientMessage::leave_lobby(state, socket),
+            ClientMessage::UpdateSettings(settings) => ClientMessage::update_settings(state, socket, settings),
+            ClientMessage::SetPassword { password } => ClientMessage::set_password(state, socket, password),
+            ClientMessage::CreateInvite { single_use, expires_in }
+                => ClientMessage::create_invite(state, socket, single_use, expires_in),
             ClientMessage::StartGame => ClientMessage::start_game(state, socket),
-            ClientMessage::Move { position } => ClientMessage::move_message(state, socket, position),
+            ClientMessage::CreateTournament { format } => ClientMessage::create_tournament(state, socket, format),
+            ClientMessage::JoinTournament { id } => ClientMessage::join_tournament(state, socket, id),
+            ClientMessage::StartTournament { id } => ClientMessage::start_tournament(state, socket, id),
+            ClientMessage::TournamentStandings { id } => ClientMessage::tournament_standings(state, id),
+            ClientMessage::Move { position, symbol } => ClientMessage::move_message(state, socket, position, symbol),
             ClientMessage::OnHover { position } => ClientMessage::on_hover(state, socket, position),
+            ClientMessage::RequestHint => ClientMessage::request_hint(state, socket),
         }
     }
 
@@ -86,29 +158,112 @@ impl ClientMessage {
         Ok(response)
     }
 
-    fn join_lobby(state: Arc<Mutex<AppState>>, socket: SocketAddr, code: String) -> Result<serde_json::Value, String> {
+    fn join_lobby(
+        state: Arc<Mutex<AppState>>,
+        socket: SocketAddr,
+        code: String,
+        password: Option<String>,
+        invite: Option<String>
+    ) -> Result<serde_json::Value, String> {
         let mut state = state.lock().unwrap();
         let session = state.socket_session.get(&socket).ok_or("couldn't find session").cloned()?;
-        let lobby = state.join_lobby(&code, session.clone()).map_err(|_| "couldn't join lobby")?;
+        let lobby = state.join_lobby(&code, session.clone(), password.as_deref(), invite.as_deref())
+            .map_err(|error| error.to_string())?;
         let lobby_guard = lobby.lock().unwrap();
         let response = json!({
             "type": "Lobby",
             "data": *lobby_guard
         });
         tracing::info!("join_lobby {}", response);
-        if let Some((sender, _)) = state.lobby_channel.get(&lobby_guard.code) {
-            let _ = sender.send(response.clone());
+        send_message(&mut state, &lobby_guard, &json!({
+            "type": "PlayerJoined",
+            "data": *lobby_guard
+        }));
+        Ok(response)
+    }
+
+    fn leave_lobby(state: Arc<Mutex<AppState>>, socket: SocketAddr) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let lobby = state.leave_lobby(&session).ok_or("session isn't in a lobby")?;
+        state.update_tournaments();
+        let response = json!({
+            "type": "LeaveLobby",
+            "data": {"code": lobby.lock().unwrap().code}
+        });
+        tracing::info!("leave_lobby {}", response);
+        Ok(response)
+    }
+
+    fn update_settings(state: Arc<Mutex<AppState>>, socket: SocketAddr, settings: LobbySettings) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let session = session.lock().unwrap().clone(); // the lobby locks its players' sessions when applying settings
+        let lobby = get_socket_lobby(&mut state, &session)?;
+        let mut lobby_guard = lobby.lock().unwrap();
+        if !lobby_guard.is_host(&session) { return Err("only the host can change the settings".to_owned()); }
+        if lobby_guard.in_game() { return Err("settings can't be changed during a game".to_owned()); }
+        settings.validate()?;
+        if lobby_guard.player_count() as usize > settings.players {
+            return Err("there are more players in the lobby than the settings have seats for".to_owned());
         }
-        send_message(&mut state, &lobby_guard, &response);
+        lobby_guard.set_settings(settings);
+        let message = json!({
+            "type": "Lobby",
+            "data": *lobby_guard
+        });
+        tracing::info!("update_settings {}", message);
+        send_message(&mut state, &lobby_guard, &message);
+        Ok(json!({}))
+    }
+
+    fn set_password(state: Arc<Mutex<AppState>>, socket: SocketAddr, password: Option<String>) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let session_guard = session.lock().unwrap();
+        let lobby = get_socket_lobby(&mut state, &session_guard)?;
+        let mut lobby_guard = lobby.lock().unwrap();
+        if !lobby_guard.is_host(&session_guard) { return Err("only the host can change the password".to_owned()); }
+        lobby_guard.set_password(password);
+        let response = json!({
+            "type": "Lobby",
+            "data": *lobby_guard
+        });
+        tracing::info!("set_password {}", response);
+        Ok(response)
+    }
+
+    fn create_invite(
+        state: Arc<Mutex<AppState>>,
+        socket: SocketAddr,
+        single_use: bool,
+        expires_in: Option<u64>
+    ) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let session_guard = session.lock().unwrap();
+        let lobby = get_socket_lobby(&mut state, &session_guard)?;
+        let mut lobby_guard = lobby.lock().unwrap();
+        if !lobby_guard.is_host(&session_guard) { return Err("only the host can create invites".to_owned()); }
+        let invite = lobby_guard.create_invite(single_use, expires_in.map(Duration::from_secs));
+        let response = json!({
+            "type": "Invite",
+            "data": {"code": lobby_guard.code, "token": invite.token, "single_use": invite.single_use, "expires_in": expires_in}
+        });
+        tracing::info!("create_invite {}", response);
         Ok(response)
     }
 
     fn start_game(state: Arc<Mutex<AppState>>, socket: SocketAddr) -> Result<serde_json::Value, String>  {
         let state = state.lock().unwrap();
         let session = state.socket_session.get(&socket).ok_or("couldn't find session based on socket").cloned()?;
-        let session_token = &session.lock().unwrap().access_token;
-        let lobby = state.session_lobby.get(session_token).ok_or("couldn't find lobby based on session").cloned()?;
+        // clone the token so the session isn't locked while the lobby serializes its players
+        let session_token = session.lock().unwrap().access_token.clone();
+        let lobby = state.session_lobby.get(&session_token).ok_or("couldn't find lobby based on session").cloned()?;
         let mut lobby_guard = lobby.lock().unwrap();
+        if !lobby_guard.is_host(&session.lock().unwrap()) { return Err("only the host can start a game".to_owned()); }
+        // restarting would wipe the board and throw the series' turn order out
+        if lobby_guard.in_game() { return Err("a game is already in progress".to_owned()); }
         lobby_guard.start_game(); // start the game attached to the lobby
         let response = json!({
             "type": "StartGame",
@@ -118,51 +273,135 @@ impl ClientMessage {
         Ok(response)
     }
 
-    fn move_message(state: Arc<Mutex<AppState>>, socket: SocketAddr, position: usize) -> Result<serde_json::Value, String> {
+    fn create_tournament(state: Arc<Mutex<AppState>>, socket: SocketAddr, format: TournamentFormat) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let tournament = state.new_tournament(format, session);
+        let response = json!({
+            "type": "Tournament",
+            "data": *tournament.lock().unwrap()
+        });
+        tracing::info!("create_tournament {}", response);
+        Ok(response)
+    }
+
+    fn join_tournament(state: Arc<Mutex<AppState>>, socket: SocketAddr, id: String) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let tournament = state.tournaments.get(&id).ok_or("couldn't find tournament").cloned()?;
+        let mut tournament_guard = tournament.lock().unwrap();
+        tournament_guard.register(session)?;
+        let response = json!({
+            "type": "Tournament",
+            "data": *tournament_guard
+        });
+        tracing::info!("join_tournament {}", response);
+        Ok(response)
+    }
+
+    fn start_tournament(state: Arc<Mutex<AppState>>, socket: SocketAddr, id: String) -> Result<serde_json::Value, String> {
+        let mut state = state.lock().unwrap();
+        let session = get_socket_session(&mut state, socket)?;
+        let session_token = session.lock().unwrap().access_token.clone();
+        let tournament = state.tournaments.get(&id).ok_or("couldn't find tournament").cloned()?;
+        {
+            let mut tournament_guard = tournament.lock().unwrap();
+            if tournament_guard.host != session_token { return Err("only the host can start the tournament".to_owned()); }
+            tournament_guard.start()?;
+            state.start_tournament_round(&mut tournament_guard);
+        }
+        state.update_tournaments(); // byes are decided straight away
+        let response = json!({
+            "type": "Tournament",
+            "data": *tournament.lock().unwrap()
+        });
+        tracing::info!("start_tournament {}", response);
+        Ok(response)
+    }
+
+    fn tournament_standings(state: Arc<Mutex<AppState>>, id: String) -> Result<serde_json::Value, String> {
         let state = state.lock().unwrap();
+        let tournament = state.tournaments.get(&id).ok_or("couldn't find tournament").cloned()?;
+        let response = json!({
+            "type": "Tournament",
+            "data": *tournament.lock().unwrap()
+        });
+        Ok(response)
+    }
+
+    fn move_message(state_handle: Arc<Mutex<AppState>>, socket: SocketAddr, position: Position, symbol: Option<Player>) -> Result<serde_json::Value, String> {
+        let mut state = state_handle.lock().unwrap();
         let session = state.socket_session.get(&socket).ok_or("couldn't find session based on socket").cloned()?;
-        let session_token = &session.lock().unwrap().access_token;
-        let lobby = state.session_lobby.get(session_token).ok_or("couldn't find lobby based on session").cloned()?;
+        let session_token = session.lock().unwrap().access_token.clone();
+        let lobby = state.session_lobby.get(&session_token).ok_or("couldn't find lobby based on session").cloned()?;
         let mut lobby_guard = lobby.lock().unwrap();
         let players = lobby_guard.players.clone();
-        match lobby_guard.game.as_mut() {
-            Some(game) => {
-                let x = players.iter().find(|player| {
-                    player.as_ref().is_some_and(|(s, p)|
-                        Arc::ptr_eq(&session, &s) && game.current_player.eq(&Some(*p))
-                )});
-                if let Some(Some((_, player))) = x {
-                    let _ = game.move_player(player, position);
-                }
-                let response = json!({
-                    "type": "Move",
-                    "data": game.board
-                });
-                tracing::info!("move_message {}", response);
-                Ok(response)
-            },
-            None => Err("game hasn't started yet".to_string()),
+        let game = lobby_guard.game.as_mut().ok_or("game hasn't started yet")?;
+        let position = position.resolve(game)?;
+        let (_, player) = players.iter().flatten()
+            .find(|(s, _)| Arc::ptr_eq(&session, s))
+            .ok_or("only players can make moves")?;
+        // a rejected move changes nothing, so only the mover hears about it
+        game.move_symbol(player, position, symbol.unwrap_or(*player)).map_err(|error| error.to_string())?;
+        let response = json!({
+            "type": "Move",
+            "data": game.board
+        });
+        tracing::info!("move_message {}", response);
+        let finished = game.is_over();
+        // spooky marks aren't on the board, so quantum games send them separately
+        let quantum = game.quantum.as_ref().map(|quantum| json!({"type": "Quantum", "data": quantum}));
+        // the finished game is reviewed once the locks are released, the solver can take a while
+        let reviewing = finished.then(|| game.clone()).map(|game| (lobby_guard.code.clone(), lobby_guard.game_id.clone(), game));
+        send_message(&mut state, &lobby_guard, &response);
+        if let Some(quantum) = quantum { send_message(&mut state, &lobby_guard, &quantum); }
+        if finished {
+            // the next game of a series starts straight away, otherwise everyone gets the final result
+            let kind = if lobby_guard.finish_game() { "StartGame" }
+                else if lobby_guard.series.is_some() { "SeriesOver" }
+                else { "GameOver" };
+            send_message(&mut state, &lobby_guard, &json!({
+                "type": kind,
+                "data": *lobby_guard
+            }));
+            drop(lobby_guard);
+            state.update_tournaments(); // the game may have decided a tournament pairing
         }
+        drop(state);
+        if let Some((code, Some(id), game)) = reviewing {
+            if let Some(review) = GameReview::of_finished(&id, &game) {
+                let mut state = state_handle.lock().unwrap();
+                state.broadcast(&code, &json!({
+                    "type": "GameReview",
+                    "data": review
+                }));
+                state.store_review(review);
+            }
+        }
+        Ok(json!({}))
     }
 
-    fn on_hover(state: Arc<Mutex<AppState>>, socket: SocketAddr, position: usize) -> Result<serde_json::Value, String> {
+    fn on_hover(state: Arc<Mutex<AppState>>, socket: SocketAddr, position: Position) -> Result<serde_json::Value, String> {
         let mut state = state.lock().unwrap();
         let session = get_socket_session(&mut state, socket)?;
         let session_guard = session.lock().unwrap();
         let lobby = get_socket_lobby(&mut state, &session_guard)?;
         let lobby_guard = lobby.lock().unwrap();
         let (_, player) = lobby_guard.players.iter().find(|player| {
-            player.as_ref().is_some_and(|(s, _)| Arc::ptr_eq(&session, &s))
+            player.as_ref().is_some_and(|(s, _)| Arc::ptr_eq(&session, s))
         }).unwrap().clone().unwrap();
         match &lobby_guard.game {
             Some(game) => {
                 if game.current_player.eq(&Some(player)) {
+                    // with gravity everyone sees where the mark would land, and a moving piece shows where it goes
+                    let position = match game.decode(position.resolve(game)?) {
+                        Some(Move::Place(position) | Move::Slide { to: position, .. } | Move::Mark { position, .. }
+                            | Move::Spooky { first: position, .. } | Move::Collapse(position)) => position,
+                        None => return Err("position is out of bounds".to_owned()),
+                    };
                     let message = json!({
                         "type": "OnHover",
-                        "data": {"symbol": match player {
-                            Player::X => "X",
-                            Player::O => "O",
-                        }, "position": position}
+                        "data": {"symbol": player, "mark": lobby_guard.settings.mark(player), "position": position}
                     });
                     send_message(&mut state, &lobby_guard, &message);
                     Ok(json!({}))
@@ -171,6 +410,35 @@ impl ClientMessage {
             None => Err("lobby doesn't have a game".to_owned()),
         }
     }
+
+    fn request_hint(state: Arc<Mutex<AppState>>, socket: SocketAddr) -> Result<serde_json::Value, String> {
+        let state = state.lock().unwrap();
+        let session = state.socket_session.get(&socket).ok_or("couldn't find session based on socket").cloned()?;
+        let session_token = session.lock().unwrap().access_token.clone();
+        let lobby = state.session_lobby.get(&session_token).ok_or("couldn't find lobby based on session").cloned()?;
+        let lobby_guard = lobby.lock().unwrap();
+        if !lobby_guard.settings.allow_hints { return Err("hints aren't allowed in this lobby".to_owned()); }
+        let game = lobby_guard.game.as_ref().ok_or("lobby doesn't have a game")?;
+        let player = lobby_guard.token_for(game.current_player.ok_or("the game is over")?);
+        if player != Some(session_token) { return Err("not this player's turn".to_owned()); }
+        // a tablebase lookup is quick, anything else searches a copy once the locks are released
+        let tablebase = state.tablebase.as_ref().and_then(|tablebase| tablebase.analyze(game));
+        let (game, settings) = (game.clone(), lobby_guard.settings.clone());
+        drop(lobby_guard);
+        drop(state);
+        // exact engines value every move, the others can only recommend one
+        let analysis = if let Some(analysis) = tablebase { analysis }
+        else if settings.engine.is_exact(&settings) { analyze(&game) } else {
+            let best = settings.engine.engine(&settings).choose_move(&game);
+            Analysis { player: game.current_player, value: None, moves: Vec::new(), best }
+        };
+        let response = json!({
+            "type": "Hint",
+            "data": analysis
+        });
+        tracing::info!("request_hint {}", response);
+        Ok(response)
+    }
 }
 
 fn get_socket_session(state: &mut MutexGuard<AppState>, socket: SocketAddr) -> Result<Arc<Mutex<Session>>, String> {
@@ -178,14 +446,12 @@ fn get_socket_session(state: &mut MutexGuard<AppState>, socket: SocketAddr) -> R
     Ok(session)
 }
 
-fn get_socket_lobby(state: &mut MutexGuard<AppState>, session: &MutexGuard<Session>) -> Result<Arc<Mutex<Lobby>>, String> {
+fn get_socket_lobby(state: &mut MutexGuard<AppState>, session: &Session) -> Result<Arc<Mutex<Lobby>>, String> {
     let session_token = &session.access_token;
     let lobby = state.session_lobby.get(session_token).ok_or("couldn't find lobby based on session").cloned()?;
     Ok(lobby)
 }
 
 fn send_message(state: &mut MutexGuard<AppState>, lobby: &MutexGuard<Lobby>, message: &Value) {
-    if let Some((sender, _)) = state.lobby_channel.get(&lobby.code) {
-        let _ = sender.send(message.clone());
-    }
+    state.broadcast(&lobby.code, message);
 }
\ No newline at end of file
@@ -1,33 +1,45 @@
-use std::{net::SocketAddr, sync::Arc, sync::Mutex};
+use std::{net::SocketAddr, sync::Arc, sync::Mutex, time::Duration};
 use axum::{
-    extract::{connect_info::ConnectInfo, ws::Message, State, WebSocketUpgrade},
+    extract::{connect_info::ConnectInfo, ws::Message, Path, State, WebSocketUpgrade},
+    http::StatusCode,
     response::Response,
-    routing::any,
-    Router
+    routing::{any, get},
+    Json, Router
 };
 use futures::{Sink, SinkExt, Stream, StreamExt};
+use game::tablebase::Tablebase;
 use messages::ClientMessage;
 use serde_json::{json, Value};
 use state::AppState;
-use tokio::{net::TcpListener, sync::broadcast::Sender};
+use tokio::{net::TcpListener, sync::{broadcast::{error::RecvError, Receiver, Sender}, mpsc}};
 
 #[cfg(test)]
 mod tests;
 mod lobby;
 mod messages;
+mod review;
+mod series;
 mod session;
+mod settings;
 mod state;
+mod tournament;
+
+const TABLEBASE_PATH: &str = "tablebase.bin";
 
 #[tokio::main]
 pub async fn main() {
-    let listener: TcpListener = TcpListener::bind("0.0.0.0:80").await.unwrap();
-    let state: AppState = AppState::new();
-    let app: Router = Router::new()
-        .route("/ws", any(handshake))
-        .with_state(Arc::new(Mutex::new(state)));
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
         .init();
+    let listener: TcpListener = TcpListener::bind("0.0.0.0:80").await.unwrap();
+    let state: Arc<Mutex<AppState>> = Arc::new(Mutex::new(AppState::new()));
+    state.lock().unwrap().tablebase = Some(load_tablebase());
+    let app: Router = Router::new()
+        .route("/ws", any(handshake))
+        .route("/tournaments/{id}", get(tournament_standings))
+        .route("/games/{id}/review", get(game_review))
+        .with_state(state.clone());
+    tokio::spawn(tournament_ticker(state));
     tracing::info!("listening on {}", listener.local_addr().unwrap());
     axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal())
@@ -35,6 +47,20 @@ pub async fn main() {
         .unwrap();
 }
 
+/// Loads the tablebase from TABLEBASE_PATH, generating and saving it there when it's missing.
+fn load_tablebase() -> Tablebase {
+    let path = std::env::var("TABLEBASE_PATH").unwrap_or_else(|_| TABLEBASE_PATH.to_owned());
+    match Tablebase::load(&path) {
+        Ok(tablebase) => tablebase,
+        Err(error) => {
+            tracing::warn!("couldn't load the tablebase from {}: {}, generating it", path, error);
+            let tablebase = Tablebase::generate();
+            if let Err(error) = tablebase.save(&path) { tracing::warn!("couldn't save the tablebase to {}: {}", path, error); }
+            tablebase
+        }
+    }
+}
+
 async fn handshake(
     ws: WebSocketUpgrade,
     ConnectInfo(address): ConnectInfo<SocketAddr>,
@@ -47,6 +73,37 @@ async fn handshake(
     })
 }
 
+async fn tournament_standings(
+    Path(id): Path<String>,
+    State(state): State<Arc<Mutex<AppState>>>
+) -> Result<Json<Value>, StatusCode> {
+    let state = state.lock().unwrap();
+    let tournament = state.tournaments.get(&id).ok_or(StatusCode::NOT_FOUND)?;
+    let tournament = json!(*tournament.lock().unwrap());
+    Ok(Json(tournament))
+}
+
+async fn game_review(
+    Path(id): Path<String>,
+    State(state): State<Arc<Mutex<AppState>>>
+) -> Result<Json<Value>, StatusCode> {
+    let state = state.lock().unwrap();
+    let review = state.review(&id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(json!(review)))
+}
+
+/// Tournament deadlines pass without anyone sending a message, so no-shows are checked on a timer.
+/// Join lockouts run out the same way, and are cleared here too.
+async fn tournament_ticker(state: Arc<Mutex<AppState>>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        interval.tick().await;
+        let mut state = state.lock().unwrap();
+        state.update_tournaments();
+        state.expire_join_attempts();
+    }
+}
+
 async fn handle_socket<
     W: Sink<Message> + Unpin,
     R: Stream<Item = Result<Message, axum::Error>> + Unpin>(
@@ -54,35 +111,65 @@ async fn handle_socket<
     socket_address: SocketAddr,
     state: Arc<Mutex<AppState>>
 ) {
-    while let Some(Ok(message)) = receiver.next().await {
-        match message {
-            Message::Text(message) => {
-                let response = match serde_json::from_str::<messages::ClientMessage>(&message) {
-                    Err(error) => { json!({"type": "Error", "data": error.to_string()}) },
-                    Ok(message) =>
-                        ClientMessage::process(message, socket_address, state.clone()).await
-                            .map_or_else(|error| json!({"type": "Error", "data": error}), |message| message)
+    // the lobby this socket is subscribed to, so broadcasts are forwarded while we keep reading client messages
+    let mut subscription: Option<(String, Receiver<Value>)> = None;
+    // the server can move a session into a lobby by itself (tournament games), and says so on this channel
+    let (direct_sender, mut direct) = mpsc::unbounded_channel::<Value>();
+    state.lock().unwrap().socket_channel.insert(socket_address, direct_sender);
+    loop {
+        tokio::select! {
+            message = receiver.next() => {
+                let Some(Ok(message)) = message else { break; };
+                if let Message::Text(message) = message {
+                    let response = match serde_json::from_str::<messages::ClientMessage>(&message) {
+                        Err(error) => { json!({"type": "Error", "data": error.to_string()}) },
+                        Ok(message) =>
+                            ClientMessage::process(message, socket_address, state.clone()).await
+                                .unwrap_or_else(|error| json!({"type": "Error", "data": error}))
+                    };
+                    if sender.send(Message::Text(response.to_string().into())).await.is_err() { break; }
                 };
-                if sender.send(Message::Text(response.to_string().into())).await.is_err() { break; }
+                // joining, creating or leaving a lobby changes which channel we should be listening to
+                let channel = get_lobby_channel(state.clone(), socket_address);
+                if channel.as_ref().map(|(code, _)| code) != subscription.as_ref().map(|(code, _)| code) {
+                    subscription = channel.map(|(code, channel)| (code, channel.subscribe()));
+                }
+            },
+            Some(message) = direct.recv() => {
+                let channel = get_lobby_channel(state.clone(), socket_address);
+                if channel.as_ref().map(|(code, _)| code) != subscription.as_ref().map(|(code, _)| code) {
+                    subscription = channel.map(|(code, channel)| (code, channel.subscribe()));
+                }
+                if sender.send(Message::text(message.to_string())).await.is_err() { break; }
             },
-            _ => {}
-        };
-        if let Some(channel) = get_lobby_channel(state.clone(), socket_address) {
-            let mut rx = channel.subscribe();
-            while let Ok(msg) = rx.recv().await {
-                tracing::info!("{}", msg);
-                if sender.send(Message::text(msg.to_string())).await.is_err() { break; }
+            broadcast = next_broadcast(&mut subscription) => {
+                match broadcast {
+                    Ok(msg) => {
+                        tracing::info!("{}", msg);
+                        if sender.send(Message::text(msg.to_string())).await.is_err() { break; }
+                    },
+                    Err(RecvError::Lagged(_)) => {},
+                    Err(RecvError::Closed) => subscription = None,
+                }
             }
         }
     }
+    state.lock().unwrap().socket_channel.remove(&socket_address);
+}
+
+async fn next_broadcast(subscription: &mut Option<(String, Receiver<Value>)>) -> Result<Value, RecvError> {
+    match subscription {
+        Some((_, rx)) => rx.recv().await,
+        None => std::future::pending().await,
+    }
 }
 
-fn get_lobby_channel(state: Arc<Mutex<AppState>>, socket_address: SocketAddr) -> Option<Sender<Value>> {
+fn get_lobby_channel(state: Arc<Mutex<AppState>>, socket_address: SocketAddr) -> Option<(String, Sender<Value>)> {
     let state = state.lock().unwrap();
     let session = state.socket_session.get(&socket_address)?.lock().unwrap();
     let lobby = state.session_lobby.get(&session.access_token)?.lock().unwrap();
     let channel = state.lobby_channel.get(&lobby.code.clone())?;
-    Some(channel.0.clone())
+    Some((lobby.code.clone(), channel.0.clone()))
 }
 
 async fn shutdown_signal() {
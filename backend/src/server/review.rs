@@ -0,0 +1,73 @@
+use game::{analysis::{legal_moves, Evaluation, Solver}, Game, Outcome, Player};
+use serde::Serialize;
+
+/// How a move compares to the best move available in its position.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Classification {
+    Best,
+    Inaccuracy,     // keeps the same result as the best move, but wins slower or loses sooner
+    MissedWin,      // a win was available and the move only draws
+    Blunder,        // the move turns a won or drawn position into a loss
+}
+
+impl Classification {
+    pub fn new(played: Evaluation, best: Evaluation) -> Self {
+        match (played, best) {
+            _ if played.score() == best.score() => Classification::Best,
+            (Evaluation::Draw, Evaluation::Win(_)) => Classification::MissedWin,
+            (Evaluation::Loss(_), Evaluation::Win(_) | Evaluation::Draw) => Classification::Blunder,
+            _ => Classification::Inaccuracy,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReviewedMove {
+    pub player: Player,
+    pub position: usize,
+    pub value: Evaluation,
+    pub best: usize,
+    pub best_value: Evaluation,
+    pub classification: Classification,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GameReview {
+    pub id: String,
+    pub outcome: Option<Outcome>,
+    pub moves: Vec<ReviewedMove>,
+}
+
+impl GameReview {
+    /// Reviews a finished game, if the solver can search it: two-player games on boards small enough to
+    /// search exhaustively, in variants it knows how to search.
+    pub fn of_finished(id: &str, game: &Game) -> Option<Self> {
+        if !game.is_over() || game.board.len() > 9 || !game.variant.is_searchable() || game.players != 2 { return None; }
+        Self::new(id, game).ok()
+    }
+    /// Replays the game's move history, comparing every move with the solver's best move. Fails when the
+    /// position the game started from can't be set up again, replaying from anywhere else would review another game.
+    pub fn new(id: &str, game: &Game) -> Result<Self, &'static str> {
+        let mut solver = Solver::default();
+        let starting_player = game.moves.first().map_or(Player::X, |(player, _)| *player);
+        let mut replay = Game::from_setup(game.variant, game.width, game.height, game.win_length, game.players, starting_player, game.setup.clone())?;
+        let mut moves = Vec::new();
+        for (player, position) in &game.moves {
+            let (best, best_value) = legal_moves(&replay).into_iter()
+                .map(|candidate| (candidate, solver.evaluate_move(&replay, player, candidate)))
+                .max_by_key(|(_, value)| value.score())
+                .unwrap_or((*position, Evaluation::Draw));
+            let value = solver.evaluate_move(&replay, player, *position);
+            moves.push(ReviewedMove {
+                player: *player,
+                position: *position,
+                value,
+                best,
+                best_value,
+                classification: Classification::new(value, best_value),
+            });
+            if replay.play(*position).is_err() { break; }
+        }
+        Ok(GameReview { id: id.to_string(), outcome: game.outcome, moves })
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// How many games the players in a lobby play before a winner is declared.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SeriesMode {
+    #[default]
+    Single,
+    BestOf(u8),     // the series ends once a player can't be caught, or after this many games
+    FirstTo(u8),    // draws don't count, the first player to this many wins takes the series
+}
+
+impl SeriesMode {
+    pub fn wins_needed(&self) -> u8 {
+        match self {
+            SeriesMode::Single => 1,
+            SeriesMode::BestOf(games) => games / 2 + 1,
+            SeriesMode::FirstTo(wins) => *wins,
+        }
+    }
+}
+
+/// The running scoreboard of a series, keyed by the players' access tokens.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Series {
+    pub mode: SeriesMode,
+    pub scores: HashMap<String, u8>,
+    pub draws: u8,
+    pub points: HashMap<String, f32>, // points scored in variants that score more than a win, like quantum
+    pub last_starter: Option<String>, // who made the first move of the previous game
+    pub winner: Option<String>,
+    pub finished: bool,
+}
+
+impl Series {
+    pub fn new(mode: SeriesMode) -> Self {
+        Series { mode, ..Default::default() }
+    }
+    pub fn games_played(&self) -> u8 {
+        self.scores.values().sum::<u8>() + self.draws
+    }
+    pub fn score(&self, token: &str) -> u8 {
+        self.scores.get(token).copied().unwrap_or(0)
+    }
+    pub fn points(&self, token: &str) -> f32 {
+        self.points.get(token).copied().unwrap_or(0.0)
+    }
+    /// Adds the points a player scored in a game, on top of the win it may count as.
+    pub fn add_points(&mut self, token: &str, points: f32) -> &mut Self {
+        *self.points.entry(token.to_string()).or_insert(0.0) += points;
+        self
+    }
+    /// Records the result of a game, `None` being a draw, and decides the series if it's over.
+    pub fn record(&mut self, winner: Option<&str>) -> &mut Self {
+        if self.finished { return self; }
+        match winner {
+            Some(token) => *self.scores.entry(token.to_string()).or_insert(0) += 1,
+            None => self.draws += 1,
+        }
+        if let Some((token, _)) = self.scores.iter().find(|(_, wins)| **wins >= self.mode.wins_needed()) {
+            self.winner = Some(token.clone());
+            self.finished = true;
+        } else if let SeriesMode::BestOf(games) = self.mode {
+            if self.games_played() >= games {
+                // out of games without anyone reaching a majority, the leader takes it unless it's tied
+                let best = self.scores.values().max().copied().unwrap_or(0);
+                let leaders: Vec<&String> = self.scores.iter()
+                    .filter(|(_, wins)| **wins == best)
+                    .map(|(token, _)| token)
+                    .collect();
+                if best > 0 && leaders.len() == 1 {
+                    self.winner = Some(leaders[0].clone());
+                }
+                self.finished = true;
+            }
+        }
+        if let SeriesMode::Single = self.mode { self.finished = true; }
+        self
+    }
+    /// Ends the series early in favour of the given player, e.g. when their opponent leaves.
+    pub fn concede(&mut self, winner: &str) -> &mut Self {
+        if !self.finished {
+            self.winner = Some(winner.to_string());
+            self.finished = true;
+        }
+        self
+    }
+}
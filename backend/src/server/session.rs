@@ -1,4 +1,5 @@
 use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde::Serialize;
 
@@ -10,6 +11,18 @@ pub struct Session {
     pub socket: SocketAddr,
 }
 
+/// Failed attempts to join a lobby from one address. They're tracked by address rather than by session,
+/// so reconnecting for a fresh session doesn't reset them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JoinAttempts {
+    pub failed: u8,                     // consecutive failed attempts to join a lobby
+    pub locked_until: Option<Instant>,
+    pub last_failure: Option<Instant>,
+}
+
+pub const MAX_FAILED_JOINS: u8 = 5;
+pub const JOIN_LOCKOUT: Duration = Duration::from_secs(60);
+
 impl Session {
     pub fn new(socket: SocketAddr, nickname: Option<String>) -> Self {
         let mut rng = thread_rng();
@@ -22,4 +35,24 @@ impl Session {
     pub fn set_nickname(&mut self, nickname: &str) {
         self.nickname = Some(String::from(nickname));
     }
-}
\ No newline at end of file
+}
+
+impl JoinAttempts {
+    pub fn is_locked(&self) -> bool {
+        self.locked_until.is_some_and(|until| Instant::now() < until)
+    }
+    /// Whether there's nothing left worth remembering: no lockout running and no recent failure.
+    pub fn is_expired(&self) -> bool {
+        !self.is_locked() && self.last_failure.is_none_or(|failure| failure.elapsed() >= JOIN_LOCKOUT)
+    }
+    /// Records a failed join attempt, locking the address out of joining lobbies
+    /// for a while once too many attempts have failed in a row.
+    pub fn record_failure(&mut self) {
+        self.failed += 1;
+        self.last_failure = Some(Instant::now());
+        if self.failed >= MAX_FAILED_JOINS {
+            self.failed = 0;
+            self.locked_until = Some(Instant::now() + JOIN_LOCKOUT);
+        }
+    }
+}
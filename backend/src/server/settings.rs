@@ -0,0 +1,200 @@
+use std::time::Duration;
+use game::{engine::{Engine, MinimaxEngine}, mcts::{MctsConfig, MctsEngine}, setup::Setup, Game, Player, Variant};
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use crate::series::SeriesMode;
+
+/// Options the host can change for the games played in their lobby.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LobbySettings {
+    pub symbols: SymbolAssignment,
+    pub starting_player: StartingPlayer,
+    pub series: SeriesMode,
+    pub allow_hints: bool,      // casual lobbies can let players ask the solver for the best move
+    pub board: BoardSize,
+    pub engine: EngineChoice,   // the engine used to search this lobby's games
+    pub variant: Variant,
+    pub players: usize,         // how many seats the lobby has, all of them playing every game
+    pub marks: Option<Vec<char>>,   // how each player's symbol is shown, one per player instead of X, O, Y and Z
+    pub position: StartingPosition, // what's on the board before the first move
+}
+
+impl Default for LobbySettings {
+    fn default() -> Self {
+        LobbySettings {
+            symbols: SymbolAssignment::default(),
+            starting_player: StartingPlayer::default(),
+            series: SeriesMode::default(),
+            allow_hints: false,
+            board: BoardSize::default(),
+            engine: EngineChoice::default(),
+            variant: Variant::default(),
+            players: 2,
+            marks: None,
+            position: StartingPosition::default(),
+        }
+    }
+}
+
+impl LobbySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.new_game(Player::X)?;
+        match self.series {
+            SeriesMode::BestOf(games) if games % 2 == 0 => return Err("a best-of series needs an odd number of games".to_string()),
+            SeriesMode::FirstTo(0) => return Err("a first-to series needs at least one win".to_string()),
+            _ => {}
+        }
+        if let StartingPlayer::Always(player) = self.starting_player {
+            if player.index() >= self.players { return Err("the starting player isn't in the game".to_string()); }
+        }
+        if let SymbolAssignment::Host(player) = self.symbols {
+            if player.index() >= self.players { return Err("the host's symbol isn't in the game".to_string()); }
+        }
+        if let Some(marks) = &self.marks {
+            if marks.len() != self.players { return Err("every player needs exactly one mark".to_string()); }
+            if marks.iter().enumerate().any(|(index, mark)| marks[..index].contains(mark)) {
+                return Err("every player needs a different mark".to_string());
+            }
+        }
+        if self.engine == EngineChoice::Minimax && !self.board.is_small() {
+            return Err("the minimax engine can only search boards of up to 9 cells".to_string());
+        }
+        if self.engine == EngineChoice::Minimax && !self.is_searchable() {
+            return Err("the minimax engine can't search this variant".to_string());
+        }
+        Ok(())
+    }
+    pub fn new_game(&self, starting_player: Player) -> Result<Game, String> {
+        let BoardSize { width, height, win_length } = self.board;
+        Game::from_setup(self.variant, width, height, win_length, self.players, starting_player, self.position.setup(&self.board))
+            .map_err(|error| error.to_string())
+    }
+    /// Whether the solver can search these games, which only works between two players.
+    pub fn is_searchable(&self) -> bool {
+        self.variant.is_searchable() && self.players == 2
+    }
+    /// How `player`'s marks are drawn for everyone in the lobby. Moves still name the symbol itself.
+    pub fn mark(&self, player: Player) -> String {
+        match &self.marks {
+            Some(marks) if self.variant != Variant::OrderChaos => marks.get(player.index()).map_or(player.to_string(), char::to_string),
+            _ => player.to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub struct BoardSize {
+    pub width: usize,
+    pub height: usize,
+    pub win_length: usize,
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        BoardSize { width: 3, height: 3, win_length: 3 }
+    }
+}
+
+impl BoardSize {
+    /// Whether exhaustive search can solve the board in reasonable time.
+    pub fn is_small(&self) -> bool {
+        self.width * self.height <= 9
+    }
+}
+
+/// What the board looks like when a lobby's game starts.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StartingPosition {
+    #[default]
+    Empty,
+    Custom(Setup),                          // blocked cells and marks placed by the host
+    Random { obstacles: usize, seed: u64 }, // blocked cells scattered from the seed, the same in every game
+}
+
+impl StartingPosition {
+    pub fn setup(&self, board: &BoardSize) -> Setup {
+        match self {
+            StartingPosition::Empty => Setup::default(),
+            StartingPosition::Custom(setup) => setup.clone(),
+            StartingPosition::Random { obstacles, seed } => Setup::random(board.width, board.height, *obstacles, *seed),
+        }
+    }
+}
+
+/// Which search engine answers questions about a lobby's games.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum EngineChoice {
+    #[default]
+    Auto,                       // minimax on small boards, MCTS on anything bigger
+    Minimax,
+    Mcts { iterations: u32 },
+}
+
+/// Players wait on hints from the engine, so MCTS always stops after this long.
+pub const ENGINE_TIME_LIMIT: Duration = Duration::from_secs(1);
+
+impl EngineChoice {
+    /// Whether the engine searches the lobby's games exhaustively, so it can give exact values for every move.
+    pub fn is_exact(&self, settings: &LobbySettings) -> bool {
+        match self {
+            _ if !settings.is_searchable() => false,
+            EngineChoice::Auto => settings.board.is_small(),
+            EngineChoice::Minimax => true,
+            EngineChoice::Mcts { .. } => false,
+        }
+    }
+    pub fn engine(&self, settings: &LobbySettings) -> Box<dyn Engine + Send> {
+        let iterations = match self {
+            _ if self.is_exact(settings) => return Box::new(MinimaxEngine::new()),
+            EngineChoice::Mcts { iterations } => *iterations,
+            _ => MctsConfig::default().iterations.unwrap_or(0),
+        };
+        Box::new(MctsEngine::new(MctsConfig {
+            iterations: Some(iterations),
+            time_limit: Some(ENGINE_TIME_LIMIT),
+            seed: thread_rng().gen(),
+            ..Default::default()
+        }))
+    }
+}
+
+/// How X and O are handed out to the players in a lobby.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SymbolAssignment {
+    #[default]
+    Random,         // a coin flip decides the host's symbol
+    Host(Player),   // the host plays as the given symbol
+    Alternate,      // the players swap symbols after every game
+}
+
+/// Which symbol makes the first move of a game, independent of who holds it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StartingPlayer {
+    Always(Player),
+    Random,
+    Alternate,      // X starts the first game, then the starting symbol passes on every game
+}
+
+impl Default for StartingPlayer {
+    fn default() -> Self {
+        StartingPlayer::Always(Player::X)
+    }
+}
+
+impl StartingPlayer {
+    /// The symbol that starts a lobby's game, given how many games the lobby has already played
+    /// and how many players there are.
+    pub fn for_game(&self, games_played: usize, players: usize) -> Player {
+        match self {
+            StartingPlayer::Always(player) => *player,
+            StartingPlayer::Random => random_player(players),
+            StartingPlayer::Alternate => Player((games_played % players) as u8),
+        }
+    }
+}
+
+pub fn random_player(players: usize) -> Player {
+    Player(thread_rng().gen_range(0..players) as u8)
+}
@@ -1,10 +1,21 @@
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde_json::{json, Value};
-use tokio::sync::broadcast::{Receiver, Sender};
-use crate::lobby::Lobby;
-use crate::session::Session;
+use game::tablebase::Tablebase;
+use tokio::sync::{broadcast::{Receiver, Sender}, mpsc::UnboundedSender};
+use crate::lobby::{JoinError, Lobby};
+use crate::review::GameReview;
+use crate::series::SeriesMode;
+use crate::session::{JoinAttempts, Session};
+use crate::settings::LobbySettings;
+use crate::tournament::{Pairing, PairingResult, Tournament, TournamentFormat, MATCH_TIMEOUT};
+
+const LOBBY_CHANNEL_CAPACITY: usize = 64;
+/// How many reviews are kept for lookups before the oldest ones are dropped.
+pub const MAX_REVIEWS: usize = 256;
 
 pub struct AppState {
     pub lobbies: HashMap<String, Arc<Mutex<Lobby>>>,    // lobbies with currently active users
@@ -12,6 +23,11 @@ pub struct AppState {
     pub session_lobby: HashMap<String, Arc<Mutex<Lobby>>>, // map sessions to current lobbies for easy lookup
     pub socket_session: HashMap<SocketAddr, Arc<Mutex<Session>>>,   // map sockets to session for easy lookup
     pub lobby_channel: HashMap<String, (Sender<Value>, Receiver<Value>)>,   // map sockets to session for easy lookup
+    pub socket_channel: HashMap<SocketAddr, UnboundedSender<Value>>,  // messages for a single socket, outside of any lobby
+    pub tournaments: HashMap<String, Arc<Mutex<Tournament>>>,   // tournaments being organised or played
+    pub reviews: VecDeque<GameReview>,  // reviews of the latest finished games, oldest first
+    pub join_attempts: HashMap<IpAddr, JoinAttempts>,   // failed joins by address, to stop brute-forcing
+    pub tablebase: Option<Tablebase>,   // solved 3x3 positions, loaded at startup
 }
 
 impl AppState {
@@ -22,7 +38,11 @@ impl AppState {
         let session_lobby: HashMap<String, Arc<Mutex<Lobby>>> = HashMap::new();
         let socket_session: HashMap<SocketAddr, Arc<Mutex<Session>>> = HashMap::new();
         let lobby_channel: HashMap<String, (Sender<Value>, Receiver<Value>)> = HashMap::new();
-        AppState { lobbies, sessions, session_lobby, socket_session, lobby_channel }
+        let socket_channel: HashMap<SocketAddr, UnboundedSender<Value>> = HashMap::new();
+        let tournaments: HashMap<String, Arc<Mutex<Tournament>>> = HashMap::new();
+        let reviews: VecDeque<GameReview> = VecDeque::new();
+        let join_attempts: HashMap<IpAddr, JoinAttempts> = HashMap::new();
+        AppState { lobbies, sessions, session_lobby, socket_session, lobby_channel, socket_channel, tournaments, reviews, join_attempts, tablebase: None }
     }
 
     pub fn new_lobby(&mut self, player_session: Arc<Mutex<Session>>) -> Arc<Mutex<Lobby>> {
@@ -30,9 +50,9 @@ impl AppState {
         let new_lobby: Arc<Mutex<Lobby>> = Arc::new(Mutex::new(lobby.clone()));
         let session_token: String = player_session.lock().unwrap().access_token.clone();
         if self.session_lobby.contains_key(&session_token) {
-            self.leave_lobby(&player_session) // leave the previous lobby
+            self.leave_lobby(&player_session); // leave the previous lobby
         }
-        let (sender, receiver) = tokio::sync::broadcast::channel::<Value>(lobby.code.parse::<usize>().unwrap());
+        let (sender, receiver) = tokio::sync::broadcast::channel::<Value>(LOBBY_CHANNEL_CAPACITY);
         let _ = sender.send(json!({"data": ""}));
         // add to both self.lobbies and self.session_lobby for lobby lookup using session
         self.lobby_channel.insert(lobby.code.clone(), (sender, receiver));
@@ -64,14 +84,43 @@ impl AppState {
         } else { None }
     }
 
-    pub fn join_lobby(&mut self, lobby_code: &str, player_session: Arc<Mutex<Session>>) -> Result<Arc<Mutex<Lobby>>, ()> {
-        if !self.lobbies.contains_key(lobby_code) { return Err(()); }
+    /// Adds the session to an existing lobby, checking the password or invite token when the lobby is private.
+    /// Failed attempts count towards a lockout of the session's address so lobby codes and passwords can't be brute-forced.
+    pub fn join_lobby(
+        &mut self,
+        lobby_code: &str,
+        player_session: Arc<Mutex<Session>>,
+        password: Option<&str>,
+        invite: Option<&str>
+    ) -> Result<Arc<Mutex<Lobby>>, JoinError> {
+        let address = player_session.lock().unwrap().socket.ip();
+        if self.join_attempts.get(&address).is_some_and(JoinAttempts::is_locked) { return Err(JoinError::TooManyAttempts); }
+        if let Err(error) = self.authorize_join(lobby_code, &player_session, password, invite) {
+            self.join_attempts.entry(address).or_default().record_failure();
+            return Err(error);
+        }
+        self.join_attempts.remove(&address);
+        self.enter_lobby(lobby_code, player_session)
+    }
+
+    /// Forgets addresses whose failed joins are old enough not to matter any more.
+    pub fn expire_join_attempts(&mut self) {
+        self.join_attempts.retain(|_, attempts| !attempts.is_expired());
+    }
+
+    /// Moves the session into the lobby without checking any credentials.
+    fn enter_lobby(&mut self, lobby_code: &str, player_session: Arc<Mutex<Session>>) -> Result<Arc<Mutex<Lobby>>, JoinError> {
         let session_token: String = player_session.lock().unwrap().access_token.clone();
+        let lobby = self.lobbies.get(lobby_code).ok_or(JoinError::LobbyNotFound).cloned()?;
+        // joining the lobby the session is already seated in changes nothing, leaving it would forfeit the game
+        let current = self.session_lobby.get(&session_token);
+        if current.is_some_and(|current| Arc::ptr_eq(current, &lobby)) && lobby.lock().unwrap().has_player(player_session.clone()) {
+            return Ok(lobby);
+        }
         // Check if the session is already in a session, then leave it
         if self.session_lobby.contains_key(&session_token) {
             self.leave_lobby(&player_session);
         };
-        let lobby = self.lobbies.get(lobby_code).ok_or(()).cloned()?;
         // Now that the user isn't in a session, add them to a session and insert into session_lobby
         self.session_lobby.insert(session_token, lobby.clone());
         self.lobbies.entry(lobby_code.to_string()).and_modify(|lobby| {
@@ -81,20 +130,171 @@ impl AppState {
         Ok(lobby)
     }
 
+    fn authorize_join(
+        &self,
+        lobby_code: &str,
+        player_session: &Arc<Mutex<Session>>,
+        password: Option<&str>,
+        invite: Option<&str>
+    ) -> Result<(), JoinError> {
+        let lobby = self.lobbies.get(lobby_code).ok_or(JoinError::LobbyNotFound)?;
+        let mut lobby_guard = lobby.lock().unwrap();
+        if lobby_guard.has_player(player_session.clone()) { return Ok(()); }
+        if lobby_guard.player_count() as usize >= lobby_guard.players.len() { return Err(JoinError::LobbyFull); }
+        if lobby_guard.entrants.contains(&player_session.lock().unwrap().access_token) { return Ok(()); }
+        lobby_guard.authorize(password, invite)
+    }
+
     /// Check if the user is currently in a lobby, and remove them from the lobby if they are.
-    pub fn leave_lobby(&mut self, session: &Arc<Mutex<Session>>) {
+    /// The remaining players are sent a `PlayerLeft` message with the updated lobby.
+    pub fn leave_lobby(&mut self, session: &Arc<Mutex<Session>>) -> Option<Arc<Mutex<Lobby>>> {
         let session_token = session.lock().unwrap().access_token.clone();
         let previous_lobby = self.session_lobby.get(&session_token).cloned();
-        if let Some(lobby) = previous_lobby {
+        if let Some(lobby) = &previous_lobby {
             let mut lobby_guard = lobby.lock().unwrap();
             if lobby_guard.has_player(session.clone()) {
                 lobby_guard.remove_player(session.clone());
                 // If the lobby becomes empty, remove it from the list of lobbies
                 if !lobby_guard.has_players() {
                     self.lobbies.remove(&lobby_guard.code);
+                    self.lobby_channel.remove(&lobby_guard.code);
+                } else {
+                    self.broadcast(&lobby_guard.code, &json!({
+                        "type": "PlayerLeft",
+                        "data": *lobby_guard
+                    }));
                 }
             }
             self.session_lobby.remove(&session_token);
         }
+        previous_lobby
+    }
+
+    pub fn broadcast(&self, lobby_code: &str, message: &Value) {
+        if let Some((sender, _)) = self.lobby_channel.get(lobby_code) {
+            let _ = sender.send(message.clone());
+        }
+    }
+
+    /// Sends a message straight to the session's socket, for things that happen to it outside of its lobby.
+    pub fn notify(&self, session: &Arc<Mutex<Session>>, message: &Value) {
+        let socket = session.lock().unwrap().socket;
+        if let Some(sender) = self.socket_channel.get(&socket) {
+            let _ = sender.send(message.clone());
+        }
+    }
+
+    /// Keeps a review around for later lookups, making room by dropping the oldest.
+    pub fn store_review(&mut self, review: GameReview) {
+        if self.reviews.len() >= MAX_REVIEWS { self.reviews.pop_front(); }
+        self.reviews.push_back(review);
+    }
+
+    pub fn review(&self, id: &str) -> Option<&GameReview> {
+        self.reviews.iter().find(|review| review.id == id)
+    }
+
+    pub fn new_tournament(&mut self, format: TournamentFormat, host: Arc<Mutex<Session>>) -> Arc<Mutex<Tournament>> {
+        let mut tournament = Tournament::new(format, host.clone());
+        let _ = tournament.register(host);
+        let id = tournament.id.clone();
+        let tournament = Arc::new(Mutex::new(tournament));
+        self.tournaments.insert(id, tournament.clone());
+        tournament
     }
-}
\ No newline at end of file
+
+    /// Opens a lobby for every pairing of the tournament's current round, moving both players into it
+    /// and starting their game. Elimination games are played until somebody wins.
+    pub fn start_tournament_round(&mut self, tournament: &mut Tournament) {
+        let entrants = tournament.entrants.clone();
+        let series = match tournament.format {
+            TournamentFormat::RoundRobin => SeriesMode::Single,
+            TournamentFormat::SingleElimination => SeriesMode::FirstTo(1),
+        };
+        let Some(round) = tournament.round_mut() else { return; };
+        for pairing in round.iter_mut().filter(|pairing| pairing.result.is_none() && pairing.lobby.is_none()) {
+            let (player, Some(opponent)) = pairing.players else { continue; };
+            let lobby = self.new_lobby(entrants[player].clone());
+            let code = lobby.lock().unwrap().code.clone();
+            let _ = self.enter_lobby(&code, entrants[opponent].clone());
+            let mut lobby_guard = lobby.lock().unwrap();
+            // nobody else should wander into a tournament game
+            let password: String = thread_rng().sample_iter(Alphanumeric).take(12).map(char::from).collect();
+            lobby_guard.set_password(Some(password));
+            // but the players themselves can always get back in after losing their connection
+            lobby_guard.entrants = [player, opponent].iter().map(|entrant| entrants[*entrant].lock().unwrap().access_token.clone()).collect();
+            lobby_guard.set_settings(LobbySettings { series, ..Default::default() });
+            lobby_guard.start_game();
+            // the players' sockets aren't listening to the new lobby yet, so tell them directly
+            let message = json!({"type": "StartGame", "data": *lobby_guard});
+            self.notify(&entrants[player], &message);
+            self.notify(&entrants[opponent], &message);
+            pairing.lobby = Some(code);
+            pairing.deadline = Some(Instant::now() + MATCH_TIMEOUT);
+        }
+    }
+
+    /// Collects the results of finished tournament games, rules on no-shows once a pairing's
+    /// deadline has passed, and opens the next round of any tournament whose round is over.
+    pub fn update_tournaments(&mut self) {
+        let tournaments: Vec<Arc<Mutex<Tournament>>> = self.tournaments.values().cloned().collect();
+        for tournament in tournaments {
+            let mut tournament = tournament.lock().unwrap();
+            if !tournament.is_started() || tournament.finished { continue; }
+            let results: Vec<Option<PairingResult>> = tournament.round().into_iter().flatten()
+                .map(|pairing| pairing.result.or_else(|| self.pairing_result(&tournament, pairing)))
+                .collect();
+            if let Some(round) = tournament.round_mut() {
+                for (pairing, result) in round.iter_mut().zip(results) {
+                    pairing.result = result;
+                }
+            }
+            while tournament.advance() {
+                self.start_tournament_round(&mut tournament);
+            }
+        }
+    }
+
+    fn pairing_result(&self, tournament: &Tournament, pairing: &Pairing) -> Option<PairingResult> {
+        let (player, Some(opponent)) = pairing.players else { return None; };
+        let code = pairing.lobby.as_ref()?;
+        let Some(lobby) = self.lobbies.get(code) else {
+            return Some(PairingResult::DoubleLoss); // both players walked away
+        };
+        let lobby = lobby.lock().unwrap();
+        let entrant = |token: &String| [player, opponent].into_iter()
+            .find(|entrant| tournament.entrants[*entrant].lock().unwrap().access_token == *token);
+        // an elimination series replays draws, so it only finishes with a winner
+        if let Some(series) = lobby.series.as_ref().filter(|series| series.finished) {
+            return Some(series.winner.as_ref().and_then(entrant).map_or(PairingResult::Draw, PairingResult::Winner));
+        }
+        if lobby.series.is_none() {
+            if let Some(outcome) = lobby.game.as_ref().and_then(|game| game.outcome) {
+                let winner = outcome.winner().and_then(|symbol| lobby.token_for(symbol));
+                return Some(winner.as_ref().and_then(entrant).map_or(PairingResult::Draw, PairingResult::Winner));
+            }
+        }
+        if pairing.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            // a player is a no-show if they left the lobby or the game is still waiting on their first move
+            let absent = |entrant: usize| {
+                let session = tournament.entrants[entrant].clone();
+                let symbol = lobby.players.iter().flatten()
+                    .find(|(s, _)| Arc::ptr_eq(s, &session))
+                    .map(|(_, symbol)| *symbol);
+                match (symbol, lobby.game.as_ref()) {
+                    (Some(symbol), Some(game)) => game.current_player == Some(symbol)
+                        && !game.board.contains(&Some(symbol)),
+                    (Some(_), None) => false,
+                    (None, _) => true,
+                }
+            };
+            return match (absent(player), absent(opponent)) {
+                (true, true) => Some(PairingResult::DoubleLoss),
+                (true, false) => Some(PairingResult::Winner(opponent)),
+                (false, true) => Some(PairingResult::Winner(player)),
+                (false, false) => None,
+            };
+        }
+        None
+    }
+}
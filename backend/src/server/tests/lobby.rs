@@ -1,5 +1,6 @@
-use std::sync::{Arc, Mutex};
-use crate::{lobby::Lobby, session::Session};
+use std::{sync::{Arc, Mutex}, time::Duration};
+use game::{Move, Outcome, Player, Variant};
+use crate::{lobby::{JoinError, Lobby}, series::SeriesMode, session::Session, settings::{BoardSize, LobbySettings, StartingPlayer, StartingPosition, SymbolAssignment}};
 use super::utils::new_socket;
 
 fn setup_session() -> Arc<Mutex<Session>> {
@@ -63,4 +64,214 @@ fn test_has_players() {
     lobby.add_player(session.clone());
     assert!(lobby.has_players());
     assert_eq!(lobby.player_count(), 1);
-}
\ No newline at end of file
+}
+#[test]
+fn test_authorize_password() {
+    let mut lobby = Lobby::new(setup_session());
+    assert!(!lobby.is_private());
+    assert_eq!(lobby.authorize(None, None), Ok(()));
+    lobby.set_password(Some(String::from("secret")));
+    assert!(lobby.is_private());
+    assert_eq!(lobby.authorize(None, None), Err(JoinError::PasswordRequired));
+    assert_eq!(lobby.authorize(Some("wrong"), None), Err(JoinError::InvalidPassword));
+    assert_eq!(lobby.authorize(Some("secret"), None), Ok(()));
+    lobby.set_password(None);
+    assert_eq!(lobby.authorize(None, None), Ok(()));
+}
+
+#[test]
+fn test_authorize_invite() {
+    let mut lobby = Lobby::new(setup_session());
+    lobby.set_password(Some(String::from("secret")));
+    let reusable = lobby.create_invite(false, None);
+    let single_use = lobby.create_invite(true, None);
+    let expired = lobby.create_invite(false, Some(Duration::ZERO));
+    assert_eq!(lobby.authorize(None, Some("not_a_token")), Err(JoinError::InvalidInvite));
+    assert_eq!(lobby.authorize(None, Some(&reusable.token)), Ok(()));
+    assert_eq!(lobby.authorize(None, Some(&reusable.token)), Ok(()));
+    assert_eq!(lobby.authorize(None, Some(&single_use.token)), Ok(()));
+    assert_eq!(lobby.authorize(None, Some(&single_use.token)), Err(JoinError::InvalidInvite));
+    assert_eq!(lobby.authorize(None, Some(&expired.token)), Err(JoinError::InviteExpired));
+    assert_eq!(lobby.authorize(None, Some(&expired.token)), Err(JoinError::InvalidInvite));
+}
+
+#[test]
+fn test_leaving_forfeits_game_and_passes_host() {
+    let host = setup_session();
+    let friend = Arc::new(Mutex::new(Session::new(new_socket(2222), Some(String::from("friend")))));
+    let mut lobby = Lobby::new(host.clone());
+    lobby.add_player(friend.clone());
+    lobby.start_game();
+    assert!(lobby.is_host(&host.lock().unwrap()));
+    let host_symbol = lobby.players[0].as_ref().unwrap().1;
+
+    lobby.remove_player(host.clone());
+    let game = lobby.game.as_ref().unwrap();
+    assert_eq!(game.outcome, Some(Outcome::Forfeit(host_symbol)));
+    assert_eq!(game.outcome.unwrap().winner(), Some(host_symbol.opponent()));
+    assert!(lobby.is_host(&friend.lock().unwrap()));
+}
+
+#[test]
+fn test_host_chooses_symbol() {
+    let host = setup_session();
+    let friend = Arc::new(Mutex::new(Session::new(new_socket(2222), Some(String::from("friend")))));
+    let mut lobby = Lobby::new(host.clone());
+    lobby.add_player(friend.clone());
+    lobby.set_settings(LobbySettings { symbols: SymbolAssignment::Host(Player::O), ..Default::default() });
+    assert_eq!(lobby.players[0].as_ref().unwrap().1, Player::O);
+    assert_eq!(lobby.players[1].as_ref().unwrap().1, Player::X);
+
+    // a host joining an empty lobby still gets their chosen symbol
+    lobby.remove_player(friend.clone());
+    lobby.remove_player(host.clone());
+    lobby.add_player(friend.clone());
+    lobby.add_player(host.clone());
+    assert_eq!(lobby.players[0].as_ref().unwrap().1, Player::X);
+    assert_eq!(lobby.players[1].as_ref().unwrap().1, Player::O);
+}
+
+#[test]
+fn test_alternate_symbols_and_starting_player() {
+    let host = setup_session();
+    let friend = Arc::new(Mutex::new(Session::new(new_socket(2222), Some(String::from("friend")))));
+    let mut lobby = Lobby::new(host.clone());
+    lobby.add_player(friend.clone());
+    lobby.set_settings(LobbySettings {
+        symbols: SymbolAssignment::Alternate,
+        starting_player: StartingPlayer::Always(Player::O),
+        ..Default::default()
+    });
+    let host_symbol = lobby.players[0].as_ref().unwrap().1;
+    lobby.start_game();
+    assert_eq!(lobby.players[0].as_ref().unwrap().1, host_symbol);
+    assert_eq!(lobby.game.as_ref().unwrap().current_player, Some(Player::O));
+    lobby.start_game();
+    assert_eq!(lobby.players[0].as_ref().unwrap().1, host_symbol.opponent());
+    assert_eq!(lobby.game.as_ref().unwrap().current_player, Some(Player::O));
+    assert_eq!(lobby.games_played, 2);
+}
+
+#[test]
+fn test_series_alternates_starter() {
+    let host = setup_session();
+    let friend = Arc::new(Mutex::new(Session::new(new_socket(2222), Some(String::from("friend")))));
+    let host_token = host.lock().unwrap().access_token.clone();
+    let mut lobby = Lobby::new(host.clone());
+    lobby.add_player(friend.clone());
+    lobby.set_settings(LobbySettings {
+        symbols: SymbolAssignment::Host(Player::X),
+        series: SeriesMode::BestOf(3),
+        ..Default::default()
+    });
+    lobby.start_game();
+    assert_eq!(lobby.game.as_ref().unwrap().current_player, Some(Player::X));
+
+    // the host wins the first game, and the friend starts the second
+    for (player, position) in [(Player::X, 0), (Player::O, 3), (Player::X, 1), (Player::O, 4), (Player::X, 2)] {
+        lobby.game.as_mut().unwrap().move_player(&player, position).unwrap();
+    }
+    assert!(lobby.finish_game());
+    assert_eq!(lobby.series.as_ref().unwrap().score(&host_token), 1);
+    assert_eq!(lobby.game.as_ref().unwrap().current_player, Some(Player::O));
+
+    // the friend leaving mid-series hands it to the host
+    lobby.remove_player(friend.clone());
+    let series = lobby.series.as_ref().unwrap();
+    assert!(series.finished);
+    assert_eq!(series.winner, Some(host_token));
+}
+
+#[test]
+fn test_order_chaos_roles() {
+    let mut lobby = Lobby::new(setup_session());
+    lobby.add_player(setup_session());
+    lobby.set_settings(LobbySettings {
+        symbols: SymbolAssignment::Host(Player::O),
+        board: BoardSize { width: 6, height: 6, win_length: 5 },
+        variant: Variant::OrderChaos,
+        ..Default::default()
+    });
+    lobby.start_game();
+    let lobby = serde_json::to_value(&lobby).unwrap();
+    // players keep their symbols, with their roles alongside
+    assert_eq!(lobby["players"][0]["symbol"], "O");
+    assert_eq!(lobby["players"][0]["role"], "Chaos");
+    assert_eq!(lobby["players"][1]["symbol"], "X");
+    assert_eq!(lobby["players"][1]["role"], "Order");
+}
+
+#[test]
+fn test_three_player_lobby() {
+    let host = setup_session();
+    let (second, third) = (setup_session(), setup_session());
+    let mut lobby = Lobby::new(host.clone());
+    lobby.add_player(second.clone());
+    lobby.set_settings(LobbySettings {
+        symbols: SymbolAssignment::Host(Player::O),
+        board: BoardSize { width: 5, height: 5, win_length: 4 },
+        variant: Variant::Elimination,
+        players: 3,
+        marks: Some(vec!['A', 'B', 'C']),
+        ..Default::default()
+    });
+    // the game waits for every seat to be filled
+    lobby.start_game();
+    assert!(lobby.game.is_none());
+    lobby.add_player(third.clone());
+    let symbols: Vec<Player> = lobby.players.iter().flatten().map(|(_, player)| *player).collect();
+    assert_eq!(symbols, [Player::O, Player::X, Player(2)]);
+    lobby.start_game();
+    assert_eq!(lobby.game.as_ref().unwrap().players, 3);
+    let serialized = serde_json::to_value(&lobby).unwrap();
+    // the custom mark is only for show, the symbol is still what moves are made with
+    assert_eq!(serialized["players"][2]["symbol"], "Y");
+    assert_eq!(serialized["players"][2]["mark"], "C");
+
+    // one player leaving drops them from the game, but the other two play on
+    lobby.remove_player(second);
+    let game = lobby.game.as_ref().unwrap();
+    assert!(!game.is_over());
+    assert_eq!(game.current_player, Some(Player::O));
+}
+
+#[test]
+fn test_random_layout_is_sent() {
+    let mut lobby = Lobby::new(setup_session());
+    lobby.add_player(setup_session());
+    lobby.set_settings(LobbySettings {
+        board: BoardSize { width: 5, height: 5, win_length: 4 },
+        position: StartingPosition::Random { obstacles: 2, seed: 7 },
+        ..Default::default()
+    });
+    assert!(serde_json::to_value(&lobby).unwrap()["setup"].is_null());
+    lobby.start_game();
+    let blocked = lobby.game.as_ref().unwrap().setup.blocked.clone();
+    assert_eq!(blocked.len(), 2);
+    assert_eq!(serde_json::to_value(&lobby).unwrap()["setup"]["blocked"], serde_json::json!(blocked));
+}
+
+#[test]
+fn test_quantum_scores_are_sent() {
+    let host = setup_session();
+    let host_token = host.lock().unwrap().access_token.clone();
+    let mut lobby = Lobby::new(host);
+    lobby.add_player(setup_session());
+    lobby.set_settings(LobbySettings {
+        symbols: SymbolAssignment::Host(Player::X),
+        series: SeriesMode::BestOf(3),
+        variant: Variant::Quantum,
+        ..Default::default()
+    });
+    lobby.start_game();
+    let game = lobby.game.as_mut().unwrap();
+    for (first, second) in [(0, 1), (3, 4), (1, 2), (4, 5), (0, 2)] {
+        game.play(game.encode(Move::Spooky { first, second })).unwrap();
+    }
+    game.play(game.encode(Move::Collapse(0))).unwrap();
+    assert_eq!(serde_json::to_value(&lobby).unwrap()["scores"], serde_json::json!([1.0, 0.0]));
+    // the series keeps a tally of the points as well as the wins
+    assert!(lobby.finish_game());
+    assert_eq!(lobby.series.as_ref().unwrap().points(&host_token), 1.0);
+    assert_eq!(serde_json::to_value(&lobby).unwrap()["series"]["points"][0], 1.0);
+}
@@ -1,9 +1,10 @@
 use std::{net::SocketAddr, sync::{Arc, Mutex}};
 use axum::extract::ws::Message;
 use futures::{channel::mpsc::{Receiver, Sender}, SinkExt, StreamExt};
+use game::{tablebase::Tablebase, Game, Move, Player, Variant};
 use serde::Deserialize;
 use serde_json::json;
-use crate::{handle_socket, session::Session, tests::utils::new_socket, AppState};
+use crate::{handle_socket, messages::{ClientMessage, Position}, session::Session, tests::utils::new_socket, AppState};
 
 fn mock_state() -> Arc<Mutex<AppState>> {
     Arc::new(Mutex::new(AppState::new()))
@@ -23,6 +24,26 @@ struct Response<T> { data: T }
 struct ResponseSession { access_token: String, nickname: Option<String> }
 #[derive(Deserialize)]
 struct ResponseLobby { code: String }
+#[derive(Deserialize)]
+struct ResponseType { r#type: String }
+
+async fn send(tx: &mut Sender<Result<Message, axum::Error>>, message: serde_json::Value) {
+    tx.send(Ok(Message::Text(message.to_string().into()))).await.unwrap();
+}
+
+/// Skips over messages until one of the given type arrives.
+async fn next_of_type(rx: &mut Receiver<Message>, kind: &str) -> serde_json::Value {
+    loop {
+        let msg = rx.next().await.unwrap();
+        let value = serde_json::from_str::<serde_json::Value>(msg.to_text().unwrap()).unwrap();
+        if value["type"] == kind { return value; }
+    }
+}
+
+async fn next_type(rx: &mut Receiver<Message>) -> String {
+    let msg = rx.next().await.unwrap();
+    serde_json::from_str::<ResponseType>(msg.to_text().unwrap()).unwrap().r#type
+}
 
 #[tokio::test]
 async fn test_multiple_new_connections() {
@@ -36,18 +57,18 @@ async fn test_multiple_new_connections() {
 
     {
         let state = state.lock().unwrap();
-        let response = serde_json::from_str::<Response<ResponseSession>>(&msg.to_text().unwrap()).unwrap().data;
+        let response = serde_json::from_str::<Response<ResponseSession>>(msg.to_text().unwrap()).unwrap().data;
         let record = state.sessions.get(&response.access_token).unwrap();
-        test_new_connection_properties(&response, &record, "keedrin");
-        let response = serde_json::from_str::<Response<ResponseSession>>(&msg2.to_text().unwrap()).unwrap().data;
+        test_new_connection_properties(&response, record, "keedrin");
+        let response = serde_json::from_str::<Response<ResponseSession>>(msg2.to_text().unwrap()).unwrap().data;
         let record = state.sessions.get(&response.access_token).unwrap();
-        test_new_connection_properties(&response, &record, "keedrin2");
+        test_new_connection_properties(&response, record, "keedrin2");
         assert!(state.sessions.len() == 2);
     }
 
     tx.send(Ok(Message::Text(json!({"type": "CreateLobby"}).to_string().into()))).await.unwrap();
     let msg = rx.next().await.unwrap();
-    let response = serde_json::from_str::<Response<ResponseLobby>>(&msg.to_text().unwrap()).unwrap().data;
+    let response = serde_json::from_str::<Response<ResponseLobby>>(msg.to_text().unwrap()).unwrap().data;
     tx2.send(Ok(Message::Text(json!({"type": "JoinLobby", "data": {"code": &response.code}}).to_string().into()))).await.unwrap();
     rx2.next().await.unwrap();
     {
@@ -69,4 +90,216 @@ fn test_new_connection_properties(
     assert_eq!(record.nickname, response.nickname);
     assert!(record.nickname.is_some());
     assert_eq!(record.nickname.unwrap(), String::from(nickname));
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn test_join_and_leave_broadcasts() {
+    let state = mock_state();
+    let (mut tx, mut rx) = setup(state.clone(), Some(new_socket(1111))).await;
+    let (mut tx2, mut rx2) = setup(state.clone(), Some(new_socket(2222))).await;
+    send(&mut tx, json!({"type": "Connection", "data": {"nickname": "host"}})).await;
+    send(&mut tx2, json!({"type": "Connection", "data": {"nickname": "guest"}})).await;
+    assert_eq!(next_type(&mut rx).await, "Session");
+    assert_eq!(next_type(&mut rx2).await, "Session");
+
+    send(&mut tx, json!({"type": "CreateLobby"})).await;
+    let msg = rx.next().await.unwrap();
+    let code = serde_json::from_str::<Response<ResponseLobby>>(msg.to_text().unwrap()).unwrap().data.code;
+    send(&mut tx2, json!({"type": "JoinLobby", "data": {"code": &code}})).await;
+    assert_eq!(next_type(&mut rx2).await, "Lobby");
+    assert_eq!(next_type(&mut rx).await, "PlayerJoined");
+
+    // the host is still able to send messages while subscribed to the lobby
+    send(&mut tx, json!({"type": "StartGame"})).await;
+    assert_eq!(next_type(&mut rx).await, "StartGame");
+    send(&mut tx2, json!({"type": "LeaveLobby"})).await;
+    assert_eq!(next_type(&mut rx2).await, "LeaveLobby");
+    let msg = rx.next().await.unwrap();
+    let left = serde_json::from_str::<serde_json::Value>(msg.to_text().unwrap()).unwrap();
+    assert_eq!(left["type"], "PlayerLeft");
+    assert_eq!(left["data"]["players"][1], serde_json::Value::Null);
+    assert!(left["data"]["outcome"]["Forfeit"].is_string());
+}
+
+#[tokio::test]
+async fn test_tournament_seats_sockets() {
+    let state = mock_state();
+    let (mut tx, mut rx) = setup(state.clone(), Some(new_socket(1111))).await;
+    let (mut tx2, mut rx2) = setup(state.clone(), Some(new_socket(2222))).await;
+    send(&mut tx, json!({"type": "Connection", "data": {"nickname": "host"}})).await;
+    send(&mut tx2, json!({"type": "Connection", "data": {"nickname": "guest"}})).await;
+    next_of_type(&mut rx, "Session").await;
+    next_of_type(&mut rx2, "Session").await;
+    send(&mut tx, json!({"type": "CreateTournament", "data": {"format": "SingleElimination"}})).await;
+    let id = next_of_type(&mut rx, "Tournament").await["data"]["id"].clone();
+    send(&mut tx2, json!({"type": "JoinTournament", "data": {"id": id}})).await;
+    next_of_type(&mut rx2, "Tournament").await;
+
+    // starting the tournament seats both entrants, and their sockets hear about it straight away
+    send(&mut tx, json!({"type": "StartTournament", "data": {"id": id}})).await;
+    let code = next_of_type(&mut rx, "StartGame").await["data"]["code"].clone();
+    assert_eq!(next_of_type(&mut rx2, "StartGame").await["data"]["code"], code);
+    // and they're listening to the new lobby
+    send(&mut tx2, json!({"type": "LeaveLobby"})).await;
+    next_of_type(&mut rx, "PlayerLeft").await;
+}
+
+#[tokio::test]
+async fn test_request_hint() {
+    let state = mock_state();
+    let (mut tx, mut rx) = setup(state.clone(), Some(new_socket(1111))).await;
+    let (mut tx2, mut rx2) = setup(state.clone(), Some(new_socket(2222))).await;
+    send(&mut tx, json!({"type": "Connection", "data": {"nickname": "host"}})).await;
+    send(&mut tx2, json!({"type": "Connection", "data": {"nickname": "guest"}})).await;
+    next_of_type(&mut rx, "Session").await;
+    next_of_type(&mut rx2, "Session").await;
+    send(&mut tx, json!({"type": "CreateLobby"})).await;
+    let lobby = next_of_type(&mut rx, "Lobby").await;
+    send(&mut tx2, json!({"type": "JoinLobby", "data": {"code": lobby["data"]["code"]}})).await;
+    next_of_type(&mut rx2, "Lobby").await;
+    next_of_type(&mut rx, "PlayerJoined").await;
+
+    send(&mut tx, json!({"type": "StartGame"})).await;
+    next_of_type(&mut rx, "StartGame").await;
+    send(&mut tx, json!({"type": "RequestHint"})).await;
+    assert_eq!(next_of_type(&mut rx, "Error").await["data"], "hints aren't allowed in this lobby");
+
+    // settings can only change between games, so leave and come back for a fresh game
+    send(&mut tx2, json!({"type": "LeaveLobby"})).await;
+    next_of_type(&mut rx2, "LeaveLobby").await;
+    send(&mut tx, json!({"type": "UpdateSettings", "data": {"symbols": {"Host": "X"}, "allow_hints": true}})).await;
+    next_of_type(&mut rx, "Lobby").await;
+    send(&mut tx2, json!({"type": "JoinLobby", "data": {"code": lobby["data"]["code"]}})).await;
+    next_of_type(&mut rx2, "Lobby").await;
+    send(&mut tx, json!({"type": "StartGame"})).await;
+    next_of_type(&mut rx, "StartGame").await;
+    send(&mut tx2, json!({"type": "RequestHint"})).await;
+    assert_eq!(next_of_type(&mut rx2, "Error").await["data"], "not this player's turn");
+    send(&mut tx, json!({"type": "RequestHint"})).await;
+    let hint = next_of_type(&mut rx, "Hint").await;
+    assert_eq!(hint["data"]["value"], "Draw");
+    assert_eq!(hint["data"]["moves"].as_array().unwrap().len(), 9);
+
+    // the tablebase answers with the same analysis as the search
+    state.lock().unwrap().tablebase = Some(Tablebase::generate());
+    send(&mut tx, json!({"type": "RequestHint"})).await;
+    assert_eq!(next_of_type(&mut rx, "Hint").await, hint);
+}
+
+#[tokio::test]
+async fn test_rejected_move() {
+    let state = mock_state();
+    let (mut tx, mut rx) = setup(state.clone(), Some(new_socket(1111))).await;
+    let (mut tx2, mut rx2) = setup(state.clone(), Some(new_socket(2222))).await;
+    send(&mut tx, json!({"type": "Connection", "data": {"nickname": "host"}})).await;
+    send(&mut tx2, json!({"type": "Connection", "data": {"nickname": "guest"}})).await;
+    next_of_type(&mut rx, "Session").await;
+    next_of_type(&mut rx2, "Session").await;
+    send(&mut tx, json!({"type": "CreateLobby"})).await;
+    let lobby = next_of_type(&mut rx, "Lobby").await;
+    send(&mut tx, json!({"type": "UpdateSettings", "data": {"symbols": {"Host": "X"}}})).await;
+    next_of_type(&mut rx, "Lobby").await;
+    send(&mut tx2, json!({"type": "JoinLobby", "data": {"code": lobby["data"]["code"]}})).await;
+    next_of_type(&mut rx2, "Lobby").await;
+    send(&mut tx, json!({"type": "StartGame"})).await;
+    next_of_type(&mut rx, "StartGame").await;
+
+    // a move out of turn comes back as an error instead of an unchanged board
+    send(&mut tx2, json!({"type": "Move", "data": {"position": 4}})).await;
+    assert_eq!(next_of_type(&mut rx2, "Error").await["data"], "Cannot move the player. It is not their turn.");
+    send(&mut tx, json!({"type": "Move", "data": {"position": 4}})).await;
+    assert_eq!(next_of_type(&mut rx, "Move").await["data"][4], "X");
+    send(&mut tx2, json!({"type": "Move", "data": {"position": 4}})).await;
+    assert_eq!(next_of_type(&mut rx2, "Error").await["data"], "A player is already at this location");
+
+    // nobody can restart a game that's under way, and only the host starts games at all
+    send(&mut tx2, json!({"type": "StartGame"})).await;
+    assert_eq!(next_of_type(&mut rx2, "Error").await["data"], "only the host can start a game");
+    send(&mut tx, json!({"type": "StartGame"})).await;
+    assert_eq!(next_of_type(&mut rx, "Error").await["data"], "a game is already in progress");
+    assert_eq!(state.lock().unwrap().lobbies.values().next().unwrap().lock().unwrap().games_played, 1);
+}
+
+#[test]
+fn test_move_positions() {
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": 4}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Cell(4), symbol: None });
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": {"board": 2, "cell": 3}}})).unwrap();
+    let ClientMessage::Move { position, .. } = message else { panic!("expected a move") };
+    assert_eq!(position, Position::Nested { board: 2, cell: 3 });
+
+    assert_eq!(position.resolve(&Game::ultimate(Player::X)), Ok(15));
+    assert!(position.resolve(&Game::new(Player::X)).is_err());
+    assert!(Position::Nested { board: 9, cell: 0 }.resolve(&Game::ultimate(Player::X)).is_err());
+    assert_eq!(Position::Cell(80).resolve(&Game::ultimate(Player::X)), Ok(80));
+}
+
+#[test]
+fn test_gravity_positions() {
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": {"column": 2}}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Column { column: 2 }, symbol: None });
+    let message: ClientMessage = serde_json::from_value(json!({"type": "OnHover", "data": {"position": {"column": 2}}})).unwrap();
+    assert_eq!(message, ClientMessage::OnHover { position: Position::Column { column: 2 } });
+
+    let mut game = Game::with_variant(Variant::Gravity, 4, 3, 3, Player::X).unwrap();
+    assert_eq!(Position::Column { column: 2 }.resolve(&game), Ok(10));
+    // hovering or clicking anywhere in a column points at its landing cell
+    assert_eq!(Position::Cell(2).resolve(&game), Ok(10));
+    for _ in 0..3 {
+        game.drop(2).unwrap();
+    }
+    assert!(Position::Column { column: 2 }.resolve(&game).is_err());
+    assert!(Position::Column { column: 4 }.resolve(&game).is_err());
+    assert!(Position::Column { column: 0 }.resolve(&Game::new(Player::X)).is_err());
+}
+
+#[test]
+fn test_slide_positions() {
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": {"from": 2, "to": 5}}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Slide { from: 2, to: 5 }, symbol: None });
+
+    let mut game = Game::with_variant(Variant::Morris { adjacent: true }, 3, 3, 3, Player::X).unwrap();
+    for position in [0, 1, 2, 4, 7, 3] {
+        game.play(Position::Cell(position).resolve(&game).unwrap()).unwrap();
+    }
+    let index = Position::Slide { from: 2, to: 5 }.resolve(&game).unwrap();
+    assert_eq!(game.decode(index), Some(Move::Slide { from: 2, to: 5 }));
+    game.play(index).unwrap();
+    assert_eq!(game.board[5], Some(Player::X));
+    assert!(Position::Slide { from: 9, to: 0 }.resolve(&game).is_err());
+    assert!(Position::Slide { from: 0, to: 1 }.resolve(&Game::new(Player::X)).is_err());
+}
+
+#[test]
+fn test_move_symbol() {
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": 4, "symbol": "O"}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Cell(4), symbol: Some(Player::O) });
+    let ClientMessage::Move { position, symbol: Some(symbol) } = message else { panic!("expected a symbol") };
+    let mut game = Game::with_variant(Variant::Wild, 3, 3, 3, Player::X).unwrap();
+    game.move_symbol(&Player::X, position.resolve(&game).unwrap(), symbol).unwrap();
+    assert_eq!(game.board[4], Some(Player::O));
+}
+
+#[test]
+fn test_notakto_positions() {
+    let game = Game::notakto(3, Player::X).unwrap();
+    assert_eq!(Position::Nested { board: 2, cell: 4 }.resolve(&game), Ok(16));
+    assert!(Position::Nested { board: 3, cell: 0 }.resolve(&game).is_err());
+    assert!(Position::Nested { board: 0, cell: 9 }.resolve(&game).is_err());
+}
+
+#[test]
+fn test_quantum_positions() {
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": {"cells": [0, 4]}}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Spooky { cells: [0, 4] }, symbol: None });
+    let message: ClientMessage = serde_json::from_value(json!({"type": "Move", "data": {"position": {"collapse": 4}}})).unwrap();
+    assert_eq!(message, ClientMessage::Move { position: Position::Collapse { collapse: 4 }, symbol: None });
+
+    let mut game = Game::with_variant(Variant::Quantum, 3, 3, 3, Player::X).unwrap();
+    for _ in 0..2 {
+        game.play(Position::Spooky { cells: [0, 4] }.resolve(&game).unwrap()).unwrap();
+    }
+    game.play(Position::Collapse { collapse: 4 }.resolve(&game).unwrap()).unwrap();
+    assert_eq!((game.board[0], game.board[4]), (Some(Player::X), Some(Player::O)));
+    assert!(Position::Spooky { cells: [0, 9] }.resolve(&game).is_err());
+    assert!(Position::Collapse { collapse: 0 }.resolve(&Game::new(Player::X)).is_err());
+}
@@ -1,5 +1,9 @@
 mod lobby;
 mod messages;
+mod review;
+mod series;
 mod session;
+mod settings;
 mod state;
+mod tournament;
 mod utils;
\ No newline at end of file
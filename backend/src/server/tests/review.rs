@@ -0,0 +1,50 @@
+use game::{analysis::Evaluation, Game, Player};
+use crate::{review::{Classification, GameReview}, state::{AppState, MAX_REVIEWS}};
+
+#[test]
+fn test_classification() {
+    assert_eq!(Classification::new(Evaluation::Win(3), Evaluation::Win(3)), Classification::Best);
+    assert_eq!(Classification::new(Evaluation::Win(5), Evaluation::Win(3)), Classification::Inaccuracy);
+    assert_eq!(Classification::new(Evaluation::Loss(2), Evaluation::Loss(4)), Classification::Inaccuracy);
+    assert_eq!(Classification::new(Evaluation::Draw, Evaluation::Win(1)), Classification::MissedWin);
+    assert_eq!(Classification::new(Evaluation::Loss(2), Evaluation::Win(1)), Classification::Blunder);
+    assert_eq!(Classification::new(Evaluation::Loss(2), Evaluation::Draw), Classification::Blunder);
+}
+
+#[test]
+fn test_review() {
+    // O answers the corner opening on an edge, then misses the block and X wins
+    let mut game = Game::new(Player::X);
+    for position in [0, 1, 4, 2, 8] { game.play(position).unwrap(); }
+    let review = GameReview::new("game", &game).unwrap();
+    assert_eq!(review.moves.len(), 5);
+    assert_eq!(review.moves[0].classification, Classification::Best);
+    assert_eq!(review.moves[1].classification, Classification::Blunder);
+    assert_eq!(review.moves[1].best_value, Evaluation::Draw);
+    assert_eq!(review.moves[4].value, Evaluation::Win(1));
+    assert_eq!(review.moves[4].classification, Classification::Best);
+}
+
+#[test]
+fn test_review_of_finished_game() {
+    let mut game = Game::new(Player::X);
+    for position in [0, 3] { game.play(position).unwrap(); }
+    assert!(GameReview::of_finished("game", &game).is_none()); // the game isn't over yet
+    for position in [1, 4, 2] { game.play(position).unwrap(); }
+    assert!(GameReview::of_finished("game", &game).is_some());
+    // a starting position that can't be set up again isn't reviewed from some other position instead
+    game.setup.blocked = vec![9];
+    assert!(GameReview::new("game", &game).is_err());
+    assert!(GameReview::of_finished("game", &game).is_none());
+}
+
+#[test]
+fn test_reviews_are_capped() {
+    let mut state = AppState::new();
+    for id in 0..=MAX_REVIEWS {
+        state.store_review(GameReview { id: id.to_string(), outcome: None, moves: Vec::new() });
+    }
+    assert_eq!(state.reviews.len(), MAX_REVIEWS);
+    assert!(state.review("0").is_none()); // the oldest review made room
+    assert!(state.review(&MAX_REVIEWS.to_string()).is_some());
+}
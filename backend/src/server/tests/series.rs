@@ -0,0 +1,45 @@
+use crate::series::{Series, SeriesMode};
+
+#[test]
+fn test_wins_needed() {
+    assert_eq!(SeriesMode::Single.wins_needed(), 1);
+    assert_eq!(SeriesMode::BestOf(3).wins_needed(), 2);
+    assert_eq!(SeriesMode::BestOf(5).wins_needed(), 3);
+    assert_eq!(SeriesMode::FirstTo(4).wins_needed(), 4);
+}
+
+#[test]
+fn test_best_of_series() {
+    let mut series = Series::new(SeriesMode::BestOf(3));
+    series.record(Some("player")).record(None);
+    assert!(!series.finished);
+    assert_eq!(series.games_played(), 2);
+    series.record(Some("player"));
+    assert!(series.finished);
+    assert_eq!(series.winner, Some(String::from("player")));
+    series.record(Some("friend")); // results after the series is decided are ignored
+    assert_eq!(series.score("friend"), 0);
+}
+
+#[test]
+fn test_best_of_series_out_of_games() {
+    let mut series = Series::new(SeriesMode::BestOf(3));
+    series.record(Some("player")).record(None).record(None);
+    assert!(series.finished);
+    assert_eq!(series.winner, Some(String::from("player")));
+
+    let mut series = Series::new(SeriesMode::BestOf(3));
+    series.record(Some("player")).record(Some("friend")).record(None);
+    assert!(series.finished);
+    assert_eq!(series.winner, None); // a tied series
+}
+
+#[test]
+fn test_first_to_series() {
+    let mut series = Series::new(SeriesMode::FirstTo(2));
+    series.record(None).record(None).record(None).record(Some("friend"));
+    assert!(!series.finished);
+    series.concede("player");
+    assert!(series.finished);
+    assert_eq!(series.winner, Some(String::from("player")));
+}
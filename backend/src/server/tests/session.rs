@@ -1,4 +1,4 @@
-use crate::{session::Session, tests::utils::new_socket};
+use crate::{session::{JoinAttempts, Session, MAX_FAILED_JOINS}, tests::utils::new_socket};
 
 #[test]
 fn test_new_session() {
@@ -14,4 +14,15 @@ fn test_set_session_nickname() {
     assert_eq!(session.nickname, Some(String::from("keedrin")));
     session.set_nickname("new_nickname");
     assert_eq!(session.nickname, Some(String::from("new_nickname")));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_failed_join_lockout() {
+    let mut attempts = JoinAttempts::default();
+    for _ in 1..MAX_FAILED_JOINS {
+        attempts.record_failure();
+        assert!(!attempts.is_locked());
+    }
+    attempts.record_failure();
+    assert!(attempts.is_locked());
+}
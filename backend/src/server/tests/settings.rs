@@ -0,0 +1,127 @@
+use game::{setup::Setup, Player, Variant};
+use crate::series::SeriesMode;
+use crate::settings::{BoardSize, EngineChoice, LobbySettings, StartingPlayer, StartingPosition, SymbolAssignment};
+
+#[test]
+fn test_default_settings() {
+    let settings = LobbySettings::default();
+    assert_eq!(settings.symbols, SymbolAssignment::Random);
+    assert_eq!(settings.starting_player, StartingPlayer::Always(Player::X));
+}
+
+#[test]
+fn test_starting_player_for_game() {
+    assert_eq!(StartingPlayer::Always(Player::O).for_game(0, 2), Player::O);
+    assert_eq!(StartingPlayer::Always(Player::O).for_game(1, 2), Player::O);
+    assert_eq!(StartingPlayer::Alternate.for_game(0, 2), Player::X);
+    assert_eq!(StartingPlayer::Alternate.for_game(1, 2), Player::O);
+    assert_eq!(StartingPlayer::Alternate.for_game(2, 2), Player::X);
+}
+
+#[test]
+fn test_deserialize_settings() {
+    let settings: LobbySettings = serde_json::from_str(r#"{"symbols": {"Host": "O"}}"#).unwrap();
+    assert_eq!(settings.symbols, SymbolAssignment::Host(Player::O));
+    assert_eq!(settings.starting_player, StartingPlayer::Always(Player::X)); // missing fields use the defaults
+    let settings: LobbySettings = serde_json::from_str(r#"{"symbols": "Alternate", "starting_player": "Random"}"#).unwrap();
+    assert_eq!(settings.symbols, SymbolAssignment::Alternate);
+    assert_eq!(settings.starting_player, StartingPlayer::Random);
+}
+
+#[test]
+fn test_validate_board_and_engine() {
+    assert!(LobbySettings::default().validate().is_ok());
+    let big_board = BoardSize { width: 7, height: 7, win_length: 4 };
+    assert!(LobbySettings { board: big_board, ..Default::default() }.validate().is_ok());
+    assert!(LobbySettings { board: big_board, engine: EngineChoice::Minimax, ..Default::default() }.validate().is_err());
+    let impossible = BoardSize { width: 3, height: 3, win_length: 5 };
+    assert!(LobbySettings { board: impossible, ..Default::default() }.validate().is_err());
+}
+
+#[test]
+fn test_variant_settings() {
+    let settings: LobbySettings = serde_json::from_str(
+        r#"{"variant": "Ultimate", "board": {"width": 9, "height": 9, "win_length": 3}}"#
+    ).unwrap();
+    assert!(settings.validate().is_ok());
+    let game = settings.new_game(Player::X).unwrap();
+    assert_eq!((game.variant, game.board.len()), (Variant::Ultimate, 81));
+    // ultimate games are always played on the full 9x9 board
+    assert!(LobbySettings { variant: Variant::Ultimate, ..Default::default() }.validate().is_err());
+
+    let settings: LobbySettings = serde_json::from_str(r#"{"variant": "Misere"}"#).unwrap();
+    assert!(settings.validate().is_ok());
+    assert_eq!(settings.new_game(Player::O).unwrap().variant, Variant::Misere);
+}
+
+#[test]
+fn test_engine_choice() {
+    let big_board = BoardSize { width: 7, height: 7, win_length: 4 };
+    let small = LobbySettings::default();
+    let big = LobbySettings { board: big_board, ..Default::default() };
+    assert!(EngineChoice::Auto.is_exact(&small));
+    assert!(!EngineChoice::Auto.is_exact(&big));
+    assert!(!EngineChoice::Mcts { iterations: 100 }.is_exact(&small));
+    assert_eq!(EngineChoice::Auto.engine(&small).name(), "minimax");
+    assert_eq!(EngineChoice::Auto.engine(&big).name(), "mcts");
+    // positions repeat in morris, so the solver can't search it however small the board is
+    let morris = LobbySettings { variant: Variant::Morris { adjacent: true }, ..Default::default() };
+    assert!(!EngineChoice::Auto.is_exact(&morris));
+    assert_eq!(EngineChoice::Auto.engine(&morris).name(), "mcts");
+    assert!(LobbySettings { engine: EngineChoice::Minimax, ..morris }.validate().is_err());
+    let game = big.new_game(Player::O).unwrap();
+    assert_eq!(game.board.len(), 49);
+    assert!(EngineChoice::Mcts { iterations: 100 }.engine(&big).choose_move(&game).is_some());
+}
+
+#[test]
+fn test_player_settings() {
+    let settings: LobbySettings = serde_json::from_str(
+        r#"{"players": 3, "board": {"width": 5, "height": 5, "win_length": 4}, "marks": ["A", "B", "C"]}"#
+    ).unwrap();
+    assert!(settings.validate().is_ok());
+    assert_eq!(settings.new_game(Player(2)).unwrap().players, 3);
+    assert_eq!(settings.mark(Player(2)), "C");
+    assert_eq!(StartingPlayer::Alternate.for_game(2, 3), Player(2));
+    // the solver only searches two-player games
+    assert!(!EngineChoice::Auto.is_exact(&settings));
+    assert!(LobbySettings { starting_player: StartingPlayer::Always(Player(3)), ..settings.clone() }.validate().is_err());
+    assert!(LobbySettings { marks: Some(vec!['A', 'A', 'C']), ..settings.clone() }.validate().is_err());
+    assert!(LobbySettings { marks: Some(vec!['A', 'B', 'C', 'D']), ..settings.clone() }.validate().is_err());
+    assert!(LobbySettings { players: 5, ..settings.clone() }.validate().is_err());
+    assert!(LobbySettings { variant: Variant::Misere, ..settings }.validate().is_err());
+}
+
+#[test]
+fn test_starting_position() {
+    let settings: LobbySettings = serde_json::from_str(
+        r#"{"position": {"Custom": {"blocked": [4], "marks": [[0, "X"], [8, "O"]]}}}"#
+    ).unwrap();
+    assert!(settings.validate().is_ok());
+    let game = settings.new_game(Player::X).unwrap();
+    assert!(game.is_blocked(4));
+    assert_eq!((game.board[0], game.board[8]), (Some(Player::X), Some(Player::O)));
+    let lopsided = StartingPosition::Custom(Setup { blocked: Vec::new(), marks: vec![(0, Player::X), (1, Player::X)] });
+    assert!(LobbySettings { position: lopsided, ..Default::default() }.validate().is_err());
+
+    // a seeded layout is the same for every game the lobby plays
+    let settings = LobbySettings {
+        board: BoardSize { width: 5, height: 5, win_length: 4 },
+        position: StartingPosition::Random { obstacles: 4, seed: 42 },
+        ..Default::default()
+    };
+    assert!(settings.validate().is_ok());
+    let (first, second) = (settings.new_game(Player::X).unwrap(), settings.new_game(Player::O).unwrap());
+    assert_eq!(first.setup.blocked.len(), 4);
+    assert_eq!(first.setup, second.setup);
+    assert!(LobbySettings { position: StartingPosition::Random { obstacles: 25, seed: 42 }, ..settings }.validate().is_err());
+}
+
+#[test]
+fn test_validate_series() {
+    assert!(LobbySettings { series: SeriesMode::BestOf(3), ..Default::default() }.validate().is_ok());
+    assert!(LobbySettings { series: SeriesMode::FirstTo(2), ..Default::default() }.validate().is_ok());
+    assert!(LobbySettings { series: SeriesMode::BestOf(0), ..Default::default() }.validate().is_err());
+    assert!(LobbySettings { series: SeriesMode::BestOf(4), ..Default::default() }.validate().is_err());
+    assert!(LobbySettings { series: SeriesMode::FirstTo(0), ..Default::default() }.validate().is_err());
+}
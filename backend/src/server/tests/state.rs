@@ -1,5 +1,5 @@
-use std::sync::{Arc, Mutex};
-use crate::{lobby::Lobby, session::Session, state::AppState, tests::utils::new_socket};
+use std::{sync::{Arc, Mutex}, time::Instant};
+use crate::{lobby::{JoinError, Lobby}, series::SeriesMode, settings::LobbySettings, session::{JoinAttempts, Session, JOIN_LOCKOUT, MAX_FAILED_JOINS}, state::AppState, tests::utils::new_socket};
 
 // TODO: make separate modules for each group of tests
 // TODO: lobby exists function to share between join_lobby, etc.
@@ -90,7 +90,7 @@ fn test_join_lobby_and_leaves_previous_lobby() {
     assert!(Arc::ptr_eq(state.session_lobby.get(&friend_token).unwrap(), &friend_lobby));
 
     // player wants to join friend's lobby, so we use the join_lobby function
-    state.join_lobby(friend_lobby_code, player.clone()).unwrap();
+    state.join_lobby(friend_lobby_code, player.clone(), None, None).unwrap();
 
     assert_eq!(state.lobbies.len(), 1);
     assert!(!state.lobbies.contains_key(player_lobby_code)); // player's previous lobby shouldn't exist anymore
@@ -134,7 +134,7 @@ fn test_leave_lobby() {
     assert!(Arc::ptr_eq(state.session_lobby.get(&friend_token).unwrap(), &friend_lobby));
 
     // player wants to join friend's lobby, so we use the join_lobby function
-    state.join_lobby(friend_lobby_code, player.clone()).unwrap();
+    state.join_lobby(friend_lobby_code, player.clone(), None, None).unwrap();
 
     assert_eq!(state.lobbies.len(), 1);
     assert!(!state.lobbies.contains_key(player_lobby_code)); // player's previous lobby shouldn't exist anymore
@@ -148,4 +148,81 @@ fn test_leave_lobby() {
     assert_eq!(friend_lobby.lock().unwrap().player_count(), 1); // only one should be in the lobby
     state.leave_lobby(&friend); // should be no players left, we the session_lobby entry and lobbies entry
     assert_eq!(friend_lobby.lock().unwrap().player_count(), 0);
-}
\ No newline at end of file
+}
+#[test]
+fn test_rejoin_own_lobby_mid_game() {
+    let mut state: AppState = AppState::new();
+    let host: Arc<Mutex<Session>> = state.new_session(new_socket(1111), Some(String::from("host")));
+    let guest: Arc<Mutex<Session>> = state.new_session(new_socket(2222), Some(String::from("guest")));
+    let lobby: Arc<Mutex<Lobby>> = state.new_lobby(host.clone());
+    let code = lobby.lock().unwrap().code.clone();
+    state.join_lobby(&code, guest.clone(), None, None).unwrap();
+    {
+        let mut lobby_guard = lobby.lock().unwrap();
+        lobby_guard.set_settings(LobbySettings { series: SeriesMode::BestOf(3), ..Default::default() });
+        lobby_guard.start_game();
+        let game = lobby_guard.game.as_mut().unwrap();
+        let starter = game.current_player.unwrap();
+        game.move_player(&starter, 4).unwrap();
+    }
+
+    // sending JoinLobby again for the lobby you're playing in must not count as leaving it
+    let rejoined = state.join_lobby(&code, guest.clone(), None, None).unwrap();
+    assert!(Arc::ptr_eq(&rejoined, &lobby));
+    let lobby_guard = lobby.lock().unwrap();
+    assert_eq!(lobby_guard.player_count(), 2);
+    let game = lobby_guard.game.as_ref().unwrap();
+    assert_eq!((game.outcome, game.moves.len()), (None, 1));
+    assert!(!lobby_guard.series.as_ref().unwrap().finished);
+}
+
+#[test]
+fn test_join_private_lobby() {
+    let mut state: AppState = AppState::new();
+    let host: Arc<Mutex<Session>> = state.new_session(new_socket(1111), Some(String::from("host")));
+    let guest: Arc<Mutex<Session>> = state.new_session(new_socket(2222), Some(String::from("guest")));
+    let lobby: Arc<Mutex<Lobby>> = state.new_lobby(host.clone());
+    let code = lobby.lock().unwrap().code.clone();
+    lobby.lock().unwrap().set_password(Some(String::from("secret")));
+
+    assert_eq!(state.join_lobby("random_code", guest.clone(), None, None).unwrap_err(), JoinError::LobbyNotFound);
+    assert_eq!(state.join_lobby(&code, guest.clone(), None, None).unwrap_err(), JoinError::PasswordRequired);
+    assert_eq!(state.join_lobby(&code, guest.clone(), Some("wrong"), None).unwrap_err(), JoinError::InvalidPassword);
+    let address = guest.lock().unwrap().socket.ip();
+    assert_eq!(state.join_attempts[&address].failed, 3);
+    state.join_lobby(&code, guest.clone(), Some("secret"), None).unwrap();
+    assert!(!state.join_attempts.contains_key(&address)); // a successful join resets the counter
+    assert_eq!(lobby.lock().unwrap().player_count(), 2);
+}
+
+#[test]
+fn test_join_lobby_lockout() {
+    let mut state: AppState = AppState::new();
+    let host: Arc<Mutex<Session>> = state.new_session(new_socket(1111), Some(String::from("host")));
+    let guest: Arc<Mutex<Session>> = state.new_session(new_socket(2222), Some(String::from("guest")));
+    let lobby: Arc<Mutex<Lobby>> = state.new_lobby(host.clone());
+    let code = lobby.lock().unwrap().code.clone();
+    lobby.lock().unwrap().set_password(Some(String::from("secret")));
+
+    for _ in 0..MAX_FAILED_JOINS {
+        assert_eq!(state.join_lobby(&code, guest.clone(), Some("guess"), None).unwrap_err(), JoinError::InvalidPassword);
+    }
+    // even the right password is refused while the address is locked out, and reconnecting doesn't help
+    assert_eq!(state.join_lobby(&code, guest.clone(), Some("secret"), None).unwrap_err(), JoinError::TooManyAttempts);
+    let reconnected: Arc<Mutex<Session>> = state.new_session(new_socket(3333), Some(String::from("guest")));
+    assert_eq!(state.join_lobby(&code, reconnected, Some("secret"), None).unwrap_err(), JoinError::TooManyAttempts);
+    assert_eq!(lobby.lock().unwrap().player_count(), 1);
+}
+
+#[test]
+fn test_expire_join_attempts() {
+    let mut state: AppState = AppState::new();
+    let (recent, old) = (new_socket(1111).ip(), "10.0.0.1".parse().unwrap());
+    state.join_attempts.entry(recent).or_default().record_failure();
+    // a lockout that ran out after the last failure was long forgotten
+    let past = Instant::now().checked_sub(JOIN_LOCKOUT * 2).unwrap();
+    state.join_attempts.insert(old, JoinAttempts { failed: 0, locked_until: Some(past), last_failure: Some(past) });
+    state.expire_join_attempts();
+    assert!(state.join_attempts.contains_key(&recent));
+    assert!(!state.join_attempts.contains_key(&old));
+}
@@ -0,0 +1,194 @@
+use std::{collections::HashSet, sync::{Arc, Mutex}, time::Instant};
+use crate::{
+    lobby::JoinError,
+    session::Session,
+    state::AppState,
+    tests::utils::new_socket,
+    tournament::{pair_up, round_robin, PairingResult, Tournament, TournamentFormat}
+};
+
+fn setup_sessions(count: u16) -> Vec<Arc<Mutex<Session>>> {
+    (0..count)
+        .map(|i| Arc::new(Mutex::new(Session::new(new_socket(1000 + i), Some(format!("player{}", i))))))
+        .collect()
+}
+
+fn setup_tournament(format: TournamentFormat, count: u16) -> Tournament {
+    let sessions = setup_sessions(count);
+    let mut tournament = Tournament::new(format, sessions[0].clone());
+    for session in sessions {
+        tournament.register(session).unwrap();
+    }
+    tournament
+}
+
+#[test]
+fn test_round_robin_schedule() {
+    for count in 2..8 {
+        let entrants: Vec<usize> = (0..count).collect();
+        let rounds = round_robin(&entrants);
+        let mut games = HashSet::new();
+        for round in &rounds {
+            // nobody plays twice in a round
+            let mut seen = HashSet::new();
+            for pairing in round {
+                assert!(seen.insert(pairing.players.0));
+                if let Some(opponent) = pairing.players.1 {
+                    assert!(seen.insert(opponent));
+                    let pair = (pairing.players.0.min(opponent), pairing.players.0.max(opponent));
+                    assert!(games.insert(pair)); // and nobody meets the same opponent twice
+                } else {
+                    assert_eq!(pairing.result, Some(PairingResult::Winner(pairing.players.0)));
+                }
+            }
+        }
+        assert_eq!(games.len(), count * (count - 1) / 2);
+    }
+}
+
+#[test]
+fn test_pair_up_gives_bye() {
+    let round = pair_up(&[0, 1, 2], &[]);
+    assert_eq!(round.len(), 2);
+    assert_eq!(round[1].players, (2, None));
+    assert_eq!(round[1].result, Some(PairingResult::Winner(2)));
+
+    // an entrant that already had a bye plays the next round
+    let round = pair_up(&[0, 1, 2], &[2]);
+    assert_eq!(round[0].players, (0, Some(2)));
+    assert_eq!(round[1].players, (1, None));
+}
+
+#[test]
+fn test_register() {
+    let mut tournament = setup_tournament(TournamentFormat::RoundRobin, 1);
+    let session = tournament.entrants[0].clone();
+    assert!(tournament.register(session).is_err());
+    assert!(tournament.start().is_err()); // not enough entrants
+}
+
+#[test]
+fn test_single_elimination() {
+    let mut tournament = setup_tournament(TournamentFormat::SingleElimination, 3);
+    tournament.start().unwrap();
+    assert!(!tournament.advance()); // the first game hasn't been played
+    tournament.round_mut().unwrap()[0].result = Some(PairingResult::Winner(1));
+    assert!(tournament.advance());
+    assert_eq!(tournament.remaining(), vec![1, 2]);
+    tournament.round_mut().unwrap()[0].result = Some(PairingResult::Winner(2));
+    assert!(!tournament.advance());
+    assert!(tournament.finished);
+    assert_eq!(tournament.champion(), Some(2));
+    let standings = tournament.standings();
+    assert!(standings[0].eliminated && standings[1].eliminated && !standings[2].eliminated);
+    assert_eq!(standings[2].wins, 2); // the bye counts as a win
+}
+
+#[test]
+fn test_round_robin_standings() {
+    let mut tournament = setup_tournament(TournamentFormat::RoundRobin, 3);
+    tournament.start().unwrap();
+    while !tournament.finished {
+        for pairing in tournament.round_mut().unwrap() {
+            if pairing.result.is_none() {
+                // the lower entrant always wins, except entrants 1 and 2 draw
+                pairing.result = Some(match pairing.players {
+                    (1, Some(2)) | (2, Some(1)) => PairingResult::Draw,
+                    (player, Some(opponent)) => PairingResult::Winner(player.min(opponent)),
+                    (player, None) => PairingResult::Winner(player),
+                });
+            }
+        }
+        tournament.advance();
+    }
+    let standings = tournament.standings();
+    assert_eq!(standings[0].played, 3);
+    assert_eq!((standings[0].wins, standings[0].draws, standings[0].losses), (3, 0, 0));
+    assert_eq!((standings[1].wins, standings[1].draws, standings[1].losses), (1, 1, 1));
+    assert_eq!(tournament.champion(), Some(0));
+}
+
+#[test]
+fn test_tournament_lobbies() {
+    let mut state = AppState::new();
+    let player = state.new_session(new_socket(1111), Some(String::from("player")));
+    let friend = state.new_session(new_socket(2222), Some(String::from("friend")));
+    let tournament = state.new_tournament(TournamentFormat::SingleElimination, player.clone());
+    let mut tournament_guard = tournament.lock().unwrap();
+    tournament_guard.register(friend.clone()).unwrap();
+    tournament_guard.start().unwrap();
+    state.start_tournament_round(&mut tournament_guard);
+    let code = tournament_guard.round().unwrap()[0].lobby.clone().unwrap();
+    drop(tournament_guard);
+
+    // both players are moved into the pairing's lobby with a game under way
+    let lobby = state.lobbies.get(&code).unwrap().clone();
+    let player_token = player.lock().unwrap().access_token.clone();
+    assert!(Arc::ptr_eq(state.session_lobby.get(&player_token).unwrap(), &lobby));
+    let starter = {
+        let mut lobby_guard = lobby.lock().unwrap();
+        assert_eq!(lobby_guard.player_count(), 2);
+        assert!(lobby_guard.is_private());
+        let game = lobby_guard.game.as_mut().unwrap();
+        let starter = game.current_player.unwrap();
+        for (symbol, position) in [(starter, 0), (starter.opponent(), 3)] {
+            game.move_player(&symbol, position).unwrap();
+        }
+        starter
+    };
+
+    // the friend's connection drops mid-game, and coming back on a new socket picks the game up where it was
+    let friend_token = friend.lock().unwrap().access_token.clone();
+    let friend = state.move_session(new_socket(4444), &friend_token).unwrap();
+    assert!(Arc::ptr_eq(&state.join_lobby(&code, friend.clone(), None, None).unwrap(), &lobby));
+    {
+        let mut lobby_guard = lobby.lock().unwrap();
+        assert_eq!(lobby_guard.player_count(), 2);
+        let winner = lobby_guard.token_for(starter).unwrap();
+        let game = lobby_guard.game.as_mut().unwrap();
+        assert_eq!((game.outcome, game.moves.len()), (None, 2));
+        for (symbol, position) in [(starter, 1), (starter.opponent(), 4), (starter, 2)] {
+            game.move_player(&symbol, position).unwrap();
+        }
+        lobby_guard.finish_game();
+        assert_eq!(lobby_guard.series.as_ref().unwrap().winner, Some(winner));
+    }
+    state.update_tournaments();
+    let tournament_guard = tournament.lock().unwrap();
+    assert!(tournament_guard.finished);
+    assert!(tournament_guard.champion().is_some());
+    drop(tournament_guard);
+
+    // an entrant who drops out can get back in without the lobby's password, but nobody else can
+    state.leave_lobby(&friend);
+    let stranger = state.new_session(new_socket(3333), Some(String::from("stranger")));
+    assert_eq!(state.join_lobby(&code, stranger, None, None).err(), Some(JoinError::PasswordRequired));
+    assert!(state.join_lobby(&code, friend.clone(), None, None).is_ok());
+}
+
+#[test]
+fn test_tournament_no_show() {
+    let mut state = AppState::new();
+    let player = state.new_session(new_socket(1111), Some(String::from("player")));
+    let friend = state.new_session(new_socket(2222), Some(String::from("friend")));
+    let tournament = state.new_tournament(TournamentFormat::RoundRobin, player.clone());
+    let mut tournament_guard = tournament.lock().unwrap();
+    tournament_guard.register(friend.clone()).unwrap();
+    tournament_guard.start().unwrap();
+    state.start_tournament_round(&mut tournament_guard);
+    let code = tournament_guard.round().unwrap()[0].lobby.clone().unwrap();
+    let lobby = state.lobbies.get(&code).unwrap().clone();
+    let starter = lobby.lock().unwrap().game.as_ref().unwrap().current_player.unwrap();
+    let mover = starter.opponent();
+    // the starter moves, then their opponent never does before the deadline passes
+    lobby.lock().unwrap().game.as_mut().unwrap().move_player(&starter, 4).unwrap();
+    tournament_guard.round_mut().unwrap()[0].deadline = Some(Instant::now());
+    drop(tournament_guard);
+    state.update_tournaments();
+
+    let tournament_guard = tournament.lock().unwrap();
+    let no_show = lobby.lock().unwrap().token_for(mover).unwrap();
+    let winner = tournament_guard.champion().unwrap();
+    assert!(tournament_guard.finished);
+    assert_ne!(tournament_guard.entrants[winner].lock().unwrap().access_token, no_show);
+}
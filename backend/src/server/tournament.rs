@@ -0,0 +1,253 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use crate::session::Session;
+
+/// How long the players of a pairing have to show up and finish before no-shows are ruled on.
+pub const MATCH_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum TournamentFormat { RoundRobin, SingleElimination }
+
+/// The result of a pairing, players being referred to by their index in `Tournament::entrants`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum PairingResult {
+    Winner(usize),
+    Draw,
+    DoubleLoss,     // neither player showed up
+}
+
+#[derive(Clone, Debug)]
+pub struct Pairing {
+    pub players: (usize, Option<usize>),  // a missing opponent is a bye
+    pub lobby: Option<String>,            // code of the lobby the game is played in
+    pub deadline: Option<Instant>,
+    pub result: Option<PairingResult>,
+}
+
+impl Pairing {
+    pub fn new(player: usize, opponent: Option<usize>) -> Self {
+        // a bye is decided as soon as it's drawn
+        let result = opponent.is_none().then_some(PairingResult::Winner(player));
+        Pairing { players: (player, opponent), lobby: None, deadline: None, result }
+    }
+    /// The entrant that lost the pairing, if it had a single loser.
+    pub fn loser(&self) -> Option<usize> {
+        match self.result? {
+            PairingResult::Winner(winner) if winner == self.players.0 => self.players.1,
+            PairingResult::Winner(_) => Some(self.players.0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Standing {
+    pub played: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub points: u32,            // three for a win or bye, one for a draw
+    pub eliminated: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    pub id: String,
+    pub format: TournamentFormat,
+    pub host: String,                       // access token of the session that created the tournament
+    pub entrants: Vec<Arc<Mutex<Session>>>,
+    pub rounds: Vec<Vec<Pairing>>,
+    pub current_round: Option<usize>,       // None until the tournament starts
+    pub finished: bool,
+}
+
+impl Tournament {
+    pub fn new(format: TournamentFormat, host: Arc<Mutex<Session>>) -> Self {
+        let id: String = thread_rng().sample_iter(Alphanumeric)
+            .take(6)
+            .map(char::from)
+            .collect();
+        let host = host.lock().unwrap().access_token.clone();
+        Tournament { id, format, host, entrants: Vec::new(), rounds: Vec::new(), current_round: None, finished: false }
+    }
+    pub fn is_started(&self) -> bool {
+        self.current_round.is_some()
+    }
+    pub fn entrant_index(&self, session: &Arc<Mutex<Session>>) -> Option<usize> {
+        self.entrants.iter().position(|entrant| Arc::ptr_eq(entrant, session))
+    }
+    pub fn register(&mut self, session: Arc<Mutex<Session>>) -> Result<(), String> {
+        if self.is_started() { return Err("the tournament has already started".to_string()); }
+        if self.entrant_index(&session).is_some() { return Err("already registered for the tournament".to_string()); }
+        self.entrants.push(session);
+        Ok(())
+    }
+    /// Draws up the schedule and opens the first round. Round robin tournaments schedule every
+    /// round up front, single elimination draws each round once the previous one is over.
+    pub fn start(&mut self) -> Result<(), String> {
+        if self.is_started() { return Err("the tournament has already started".to_string()); }
+        if self.entrants.len() < 2 { return Err("a tournament needs at least two entrants".to_string()); }
+        let entrants: Vec<usize> = (0..self.entrants.len()).collect();
+        self.rounds = match self.format {
+            TournamentFormat::RoundRobin => round_robin(&entrants),
+            TournamentFormat::SingleElimination => vec![pair_up(&entrants, &[])],
+        };
+        self.current_round = Some(0);
+        Ok(())
+    }
+    pub fn round(&self) -> Option<&Vec<Pairing>> {
+        self.rounds.get(self.current_round?)
+    }
+    pub fn round_mut(&mut self) -> Option<&mut Vec<Pairing>> {
+        self.rounds.get_mut(self.current_round?)
+    }
+    pub fn is_round_complete(&self) -> bool {
+        self.round().is_some_and(|round| round.iter().all(|pairing| pairing.result.is_some()))
+    }
+    /// Entrants still in contention, everybody in a round robin.
+    pub fn remaining(&self) -> Vec<usize> {
+        let mut eliminated: Vec<usize> = Vec::new();
+        for pairing in self.rounds.iter().flatten() {
+            match pairing.result {
+                Some(PairingResult::DoubleLoss) => {
+                    eliminated.push(pairing.players.0);
+                    eliminated.extend(pairing.players.1);
+                },
+                _ => eliminated.extend(pairing.loser()),
+            }
+        }
+        match self.format {
+            TournamentFormat::RoundRobin => (0..self.entrants.len()).collect(),
+            TournamentFormat::SingleElimination => (0..self.entrants.len())
+                .filter(|entrant| !eliminated.contains(entrant))
+                .collect(),
+        }
+    }
+    /// Entrants that have been given a bye so far.
+    pub fn byes(&self) -> Vec<usize> {
+        self.rounds.iter().flatten()
+            .filter(|pairing| pairing.players.1.is_none())
+            .map(|pairing| pairing.players.0)
+            .collect()
+    }
+    /// Moves on to the next round once every pairing of the current one has a result.
+    /// Returns whether a new round was opened.
+    pub fn advance(&mut self) -> bool {
+        if self.finished || !self.is_round_complete() { return false; }
+        let next = self.current_round.map_or(0, |round| round + 1);
+        if self.format == TournamentFormat::SingleElimination {
+            let remaining = self.remaining();
+            if remaining.len() > 1 { self.rounds.push(pair_up(&remaining, &self.byes())); }
+        }
+        if next < self.rounds.len() {
+            self.current_round = Some(next);
+            true
+        } else {
+            self.finished = true;
+            false
+        }
+    }
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut standings = vec![Standing::default(); self.entrants.len()];
+        for pairing in self.rounds.iter().flatten() {
+            let Some(result) = pairing.result else { continue; };
+            let (player, opponent) = pairing.players;
+            let players: Vec<usize> = [Some(player), opponent].into_iter().flatten().collect();
+            for entrant in players {
+                let standing = &mut standings[entrant];
+                standing.played += 1;
+                match result {
+                    PairingResult::Winner(winner) if winner == entrant => { standing.wins += 1; standing.points += 3; },
+                    PairingResult::Draw => { standing.draws += 1; standing.points += 1; },
+                    _ => standing.losses += 1,
+                }
+            }
+        }
+        if self.format == TournamentFormat::SingleElimination {
+            let remaining = self.remaining();
+            for (entrant, standing) in standings.iter_mut().enumerate() {
+                standing.eliminated = !remaining.contains(&entrant);
+            }
+        }
+        standings
+    }
+    /// The overall winner of a finished tournament: the last one standing, or the round robin points leader.
+    pub fn champion(&self) -> Option<usize> {
+        if !self.finished { return None; }
+        match self.format {
+            TournamentFormat::SingleElimination => {
+                let remaining = self.remaining();
+                (remaining.len() == 1).then(|| remaining[0])
+            },
+            TournamentFormat::RoundRobin => {
+                let standings = self.standings();
+                let best = standings.iter().map(|standing| standing.points).max()?;
+                let leaders: Vec<usize> = (0..standings.len()).filter(|i| standings[*i].points == best).collect();
+                (leaders.len() == 1).then(|| leaders[0])
+            },
+        }
+    }
+}
+
+/// Every entrant plays every other entrant once, scheduled with the circle method.
+pub fn round_robin(entrants: &[usize]) -> Vec<Vec<Pairing>> {
+    let mut seats: Vec<Option<usize>> = entrants.iter().copied().map(Some).collect();
+    if seats.len() % 2 == 1 { seats.push(None); }
+    let seat_count = seats.len();
+    let mut rounds = Vec::new();
+    for _ in 1..seat_count {
+        let round = (0..seat_count / 2)
+            .filter_map(|i| match (seats[i], seats[seat_count - 1 - i]) {
+                (Some(player), opponent) | (opponent, Some(player)) => Some(Pairing::new(player, opponent)),
+                (None, None) => None,
+            })
+            .collect();
+        rounds.push(round);
+        seats[1..].rotate_right(1); // the first seat stays put while everyone else moves along
+    }
+    rounds
+}
+
+/// Pairs entrants in order. With an odd number the bye goes to the last entrant that hasn't had one
+/// yet, so the same entrant doesn't sit out every round.
+pub fn pair_up(entrants: &[usize], had_bye: &[usize]) -> Vec<Pairing> {
+    let mut entrants = entrants.to_vec();
+    let bye = (entrants.len() % 2 == 1).then(|| {
+        let index = entrants.iter().rposition(|entrant| !had_bye.contains(entrant)).unwrap_or(entrants.len() - 1);
+        entrants.remove(index)
+    });
+    let mut round: Vec<Pairing> = entrants.chunks(2)
+        .map(|pair| Pairing::new(pair[0], pair.get(1).copied()))
+        .collect();
+    round.extend(bye.map(|entrant| Pairing::new(entrant, None)));
+    round
+}
+
+impl Serialize for Tournament {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        #[derive(Serialize)]
+        struct SerializedEntrant { nickname: Option<String>, port: u16, standing: Standing }
+        #[derive(Serialize)]
+        struct SerializedPairing<'a> { players: (usize, Option<usize>), lobby: &'a Option<String>, result: Option<PairingResult> }
+        let standings = self.standings();
+        let entrants: Vec<SerializedEntrant> = self.entrants.iter().zip(standings).map(|(session, standing)| {
+            let session = session.lock().unwrap();
+            SerializedEntrant { nickname: session.nickname.clone(), port: session.socket.port(), standing }
+        }).collect();
+        let rounds: Vec<Vec<SerializedPairing>> = self.rounds.iter().map(|round| round.iter().map(|pairing|
+            SerializedPairing { players: pairing.players, lobby: &pairing.lobby, result: pairing.result }
+        ).collect()).collect();
+        let mut s = serializer.serialize_struct("Tournament", 7)?;
+        s.serialize_field("id", &self.id)?;
+        s.serialize_field("format", &self.format)?;
+        s.serialize_field("entrants", &entrants)?;
+        s.serialize_field("rounds", &rounds)?;
+        s.serialize_field("current_round", &self.current_round)?;
+        s.serialize_field("finished", &self.finished)?;
+        s.serialize_field("champion", &self.champion())?;
+        s.end()
+    }
+}